@@ -1,40 +1,148 @@
 use std::{
+    collections::HashMap,
     error::Error,
     fmt::Display,
     io::{self, prelude::*, BufReader},
 };
 use std::{fs::File, path::Path};
 
-use wordle_searcher::solvers::fixed_size_wordle_solver::*;
+use wordle_searcher::solvers::{
+    strategy::StrategyRegistry,
+    wordle::{
+        dynamic_wordle_solver::{DynamicWordleLine, WordleSolver},
+        fixed_size_wordle_solver::{
+            AddGuessErrorVariant, Contradiction, Elimination, LineViolation, WordleCharStatus,
+        },
+        ranking::GuessMetric,
+    },
+    Solver,
+};
+
+const DEFAULT_WORD_LENGTH: usize = 5;
+const OPTIMAL_SEARCH_LIMIT: usize = 30;
 
 fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-    //let args: Vec<String> = std::env::args().collect();
-    //let path = &args[1];
-    let path = r"C:\Users\Vabka\Downloads\russian_nouns_v2.0\russian_nouns.txt";
+    let args: Vec<String> = std::env::args().collect();
+    let path = args
+        .get(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map(String::as_str)
+        .unwrap_or(r"C:\Users\Vabka\Downloads\russian_nouns_v2.0\russian_nouns.txt");
 
-    let corpus = read_all_lines_lowercase_with_exact_length(path, 5)?;
-    let mut game: NaiveSolver<5> = NaiveSolver::new(corpus);
+    let word_length = match option_value(&args, "--length") {
+        Some(length) => length.parse()?,
+        None => DEFAULT_WORD_LENGTH,
+    };
+    let corpus = read_all_lines_lowercase_with_exact_length(path, word_length)?;
+    let allowed_guesses = match option_value(&args, "--guesses") {
+        Some(guesses_path) => {
+            read_all_lines_lowercase_with_exact_length(guesses_path, word_length)?
+        }
+        None => vec![],
+    };
+    let mut game = WordleSolver::with_allowed_guesses(word_length, corpus, allowed_guesses)?;
+    game.set_hard_mode(args.iter().any(|arg| arg == "--hard"));
+    if let Some(attempts) = option_value(&args, "--attempts") {
+        game.set_max_attempts(attempts.parse()?);
+    }
+    if let Some(name) = option_value(&args, "--strategy") {
+        let strategies = StrategyRegistry::with_builtin();
+        match strategies.get(name) {
+            Some(strategy) => game.set_strategy(strategy),
+            None => {
+                eprintln!("Available strategies:");
+                for strategy in strategies.iter() {
+                    eprintln!("  {} - {}", strategy.name(), strategy.description());
+                }
+                return Err(format!("unknown strategy {}", name).into());
+            }
+        }
+    }
+    if let Some(frequencies_path) = option_value(&args, "--frequencies") {
+        game.set_weights(&read_frequencies(frequencies_path)?, 1.0);
+    }
+    let cache_path = format!("{}.{}.patterns", path, word_length);
+    game.use_pattern_cache(cache_path);
     loop {
-        let guess = get_guess()?;
-        if let Err(e) = game.add_guess(guess) {
-            match e.error {
-                AddGuessErrorVariant::WordLength { expected_length: _ } => {
-                    eprintln!("Invalid guess length!");
+        match get_command()? {
+            Command::Guess(guess) => {
+                if let Err(e) = game.add_constraint(guess) {
+                    match e.error {
+                        AddGuessErrorVariant::WordLength { expected_length: _ } => {
+                            eprintln!("Invalid guess length!");
+                            continue;
+                        }
+                        AddGuessErrorVariant::NoMoreAttempts { total_attempts: _ } => {
+                            eprintln!("No more attempts!");
+                            break;
+                        }
+                        AddGuessErrorVariant::HardMode { violation } => {
+                            eprintln!("Hard mode: {}!", violation);
+                            continue;
+                        }
+                        AddGuessErrorVariant::BreaksLaterGuess { line, violation } => {
+                            eprintln!("Hard mode: in guess {}, {}!", line + 1, violation);
+                            continue;
+                        }
+                    }
+                }
+            }
+            Command::Undo => {
+                if game.pop_guess().is_none() {
+                    eprintln!("Nothing to undo!");
+                    continue;
+                }
+            }
+            Command::Redo => match game.redo() {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    eprintln!("{}!", e);
+                    continue;
+                }
+                None => {
+                    eprintln!("Nothing to redo!");
                     continue;
                 }
-                AddGuessErrorVariant::NoMoreAttempts { total_attempts: _ } => {
-                    eprintln!("No more attempts!");
-                    break;
+            },
+            Command::History => {
+                for (i, line) in game.history().iter().enumerate() {
+                    println!("{}. {} {}", i + 1, line.word(), mask_of(line));
                 }
+                continue;
+            }
+            Command::Replace { number, guess } => {
+                let replaced = number
+                    .checked_sub(1)
+                    .and_then(|index| game.replace_guess(index, guess));
+                match replaced {
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        eprintln!("{}!", e);
+                        continue;
+                    }
+                    None => {
+                        eprintln!("No such guess!");
+                        continue;
+                    }
+                }
+            }
+            Command::Why(word) => {
+                match game.explain(&word) {
+                    Some(reason) => println!("{} не подходит: {}", word, describe(&reason)),
+                    None => println!("{} ещё может быть ответом", word),
+                }
+                continue;
+            }
+            Command::Unknown(command) => {
+                eprintln!("Unknown command {}!", command);
+                continue;
             }
         }
-        let mut count = 0;
-        println!("Возможные варианты:");
-        for word in game.iter_corpus() {
-            println!("{}", word);
-            count += 1;
+        let count = print_possible_guesses(&game);
+        if let Some(contradiction) = game.contradiction() {
+            print_contradiction(&game, &contradiction);
+            continue;
         }
-        println!("Всего {}", count);
         if count <= 1 {
             println!(
                 "Больше подсказать не могу. Возможно решено за {}",
@@ -42,49 +150,240 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
             );
             break;
         }
+        print_recommendations(&game);
+        if let Some(e) = game.take_pattern_cache_error() {
+            eprintln!("Pattern cache is unavailable: {}", e);
+        }
+        if count <= OPTIMAL_SEARCH_LIMIT {
+            if let Some(tree) = game.optimal_decision_tree(game.remaining_attempts()) {
+                println!(
+                    "Оптимальный ход: {} (в среднем ещё {:.2} попыток)",
+                    tree.guess, tree.expected_guesses
+                );
+            }
+        }
         println!("================================");
     }
     Ok(())
 }
 
-fn get_guess<const WORD_LENGTH: usize>() -> Result<WordleLine<WORD_LENGTH>, GuessError> {
-    println!("Введённое слово: ");
-    let mut word = String::with_capacity(12);
-    io::stdin().read_line(&mut word)?;
-    let trimmed_word = word.trim();
+fn print_possible_guesses<S: Solver>(game: &S) -> usize
+where
+    for<'a> S::Guess<'a>: Display,
+{
+    println!("Возможные варианты:");
+    for word in game.iter_possible_guesses() {
+        println!("{}", word);
+    }
+    let count = game.possible_guess_count();
+    println!("Всего {}", count);
+    count
+}
+
+fn print_recommendations<S: Solver<GuessMetric = GuessMetric>>(game: &S)
+where
+    for<'a> S::Guess<'a>: Display,
+{
+    println!("Рекомендуемые слова:");
+    for (word, metric) in game.iter_best_next_guess().take(10) {
+        let finish = match (metric.expected_guesses, metric.win_probability) {
+            (Some(guesses), Some(win)) => {
+                format!(
+                    ", ещё ~{:.2} попыток, шанс победы ~{:.0}%",
+                    guesses,
+                    win * 100.0
+                )
+            }
+            _ => String::new(),
+        };
+        println!(
+            "{}{} ({:.2} бит, вариантов ответа {}, в среднем останется {:.1}, в худшем {}{})",
+            word,
+            if metric.is_candidate { "*" } else { "" },
+            metric.entropy,
+            metric.buckets,
+            metric.expected_remaining,
+            metric.largest_bucket,
+            finish
+        );
+    }
+}
 
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+enum Command {
+    Guess(DynamicWordleLine),
+    Undo,
+    Redo,
+    History,
+    /// Replaces the guess with the 1-based `number`.
+    Replace {
+        number: usize,
+        guess: DynamicWordleLine,
+    },
+    /// Asks why the word is not among the possible answers.
+    Why(String),
+    Unknown(String),
+}
+
+fn get_command() -> Result<Command, GuessError> {
+    println!("Введённое слово (или :undo, :redo, :history, :replace <номер>, :why <слово>): ");
+    let word = read_line()?;
+    let mut parts = word.split_whitespace();
+    let command = match parts.next() {
+        Some(":undo") => Command::Undo,
+        Some(":redo") => Command::Redo,
+        Some(":history") => Command::History,
+        Some(":replace") => match parts.next().and_then(|number| number.parse().ok()) {
+            Some(number) => {
+                println!("Введённое слово: ");
+                let word = read_line()?;
+                Command::Replace {
+                    number,
+                    guess: get_guess(&word)?,
+                }
+            }
+            None => Command::Unknown(word),
+        },
+        Some(":why") => match parts.next() {
+            Some(word) => Command::Why(word.to_lowercase()),
+            None => Command::Unknown(word),
+        },
+        Some(command) if command.starts_with(':') => Command::Unknown(word),
+        _ => Command::Guess(get_guess(&word)?),
+    };
+    Ok(command)
+}
+
+fn read_line() -> io::Result<String> {
+    let mut line = String::with_capacity(12);
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim().to_string())
+}
+
+fn get_guess(trimmed_word: &str) -> Result<DynamicWordleLine, GuessError> {
     println!("Маска (*-не угадано. ?-не на своём месте): ");
-    let mut mask = String::with_capacity(12);
-    io::stdin().read_line(&mut mask)?;
-    let trimmed_mask = mask.trim();
+    let mask = read_line()?;
+    let trimmed_mask = mask.as_str();
 
     let word_chars = trimmed_word.chars();
     let mask_chars = trimmed_mask.chars();
     if word_chars.count() == mask_chars.count() {
-        let mut vec = Vec::with_capacity(WORD_LENGTH);
-        vec.extend(
-            trimmed_word
-                .chars()
-                .zip(trimmed_mask.chars())
-                .filter_map(|(ch, mask_ch)| {
-                    if mask_ch == '*' {
-                        Some((ch, WordleCharStatus::Inexistent))
-                    } else if mask_ch == '?' {
-                        Some((ch, WordleCharStatus::Existing))
-                    } else if mask_ch == ch {
-                        Some((ch, WordleCharStatus::Good))
-                    } else {
-                        None
-                    }
-                }),
-        );
+        let chars = trimmed_word
+            .chars()
+            .zip(trimmed_mask.chars())
+            .filter_map(|(ch, mask_ch)| {
+                if mask_ch == '*' {
+                    Some((ch, WordleCharStatus::Inexistent))
+                } else if mask_ch == '?' {
+                    Some((ch, WordleCharStatus::Existing))
+                } else if mask_ch == ch {
+                    Some((ch, WordleCharStatus::Good))
+                } else {
+                    None
+                }
+            })
+            .collect();
 
-        Ok(WordleLine::new(vec.to_fixed_sized_array().unwrap()))
+        Ok(DynamicWordleLine::new(chars))
     } else {
         Err(GuessError::WordMaskLength)
     }
 }
 
+fn print_contradiction(game: &WordleSolver, contradiction: &Contradiction) {
+    let history = game.history();
+    println!(
+        "Ни одно слово не подходит начиная с попытки {} ({})",
+        contradiction.line + 1,
+        history[contradiction.line].word()
+    );
+    if contradiction.fixes.is_empty() {
+        println!("Исправить одной буквой маски не получится, попробуйте :undo или :replace");
+        return;
+    }
+    println!("Возможно, в маске ошибка (исправить можно через :replace):");
+    for fix in contradiction.fixes.iter().take(5) {
+        let mut fixed = history[fix.line].clone();
+        fixed.chars[fix.position].1 = fix.status;
+        println!(
+            "попытка {}: {} -> {} (вариантов {})",
+            fix.line + 1,
+            mask_of(&history[fix.line]),
+            mask_of(&fixed),
+            fix.candidates
+        );
+    }
+}
+
+/// Mask of a line in the same format it is entered.
+fn describe(reason: &Elimination) -> String {
+    match reason {
+        Elimination::WordLength {
+            expected_length,
+            length,
+        } => format!("длина слова {} вместо {}", length, expected_length),
+        Elimination::NotAnAnswer => "его нет в списке возможных ответов".to_string(),
+        Elimination::Excluded => "оно исключено".to_string(),
+        Elimination::Guess {
+            line,
+            guess,
+            violation,
+        } => {
+            let violation = match violation {
+                LineViolation::WordLength { expected_length } => {
+                    format!("длина слова должна быть {}", expected_length)
+                }
+                LineViolation::MissingGood { letter, position } => {
+                    format!("буква {} должна стоять на позиции {}", letter, position + 1)
+                }
+                LineViolation::LetterAtPosition { letter, position } => {
+                    format!(
+                        "буква {} не может стоять на позиции {}",
+                        letter,
+                        position + 1
+                    )
+                }
+                LineViolation::TooFew {
+                    letter,
+                    required,
+                    found,
+                } => format!(
+                    "буква {} должна встретиться хотя бы {} раз, а встречается {}",
+                    letter, required, found
+                ),
+                LineViolation::TooMany {
+                    letter,
+                    allowed,
+                    found,
+                } => format!(
+                    "буква {} может встретиться не больше {} раз, а встречается {}",
+                    letter, allowed, found
+                ),
+            };
+            format!("попытка {} ({}): {}", line + 1, guess, violation)
+        }
+    }
+}
+
+fn mask_of(line: &DynamicWordleLine) -> String {
+    line.chars
+        .iter()
+        .map(|(ch, status)| match status {
+            WordleCharStatus::Inexistent => '*',
+            WordleCharStatus::Existing => '?',
+            WordleCharStatus::Good => *ch,
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 enum GuessError {
     IO,
@@ -113,30 +412,29 @@ fn read_all_lines_lowercase_with_exact_length(
     let reader = BufReader::new(file);
     let mut corpus = vec![];
     for line in reader.lines() {
-        if let Ok(line) = line {
-            let trimmed = line.trim().to_lowercase();
-            if trimmed.chars().count() == length {
-                corpus.push(trimmed.to_string());
-            }
+        // lines that are not valid text are skipped
+        let Ok(line) = line else { continue };
+        let trimmed = line.trim().to_lowercase();
+        if trimmed.chars().count() == length {
+            corpus.push(trimmed.to_string());
         }
     }
     Ok(corpus)
 }
 
-trait ToFixedSizedArray {
-    type Item;
-    type Error;
-    fn to_fixed_sized_array<const SIZE: usize>(self) -> Result<[Self::Item; SIZE], Self::Error>;
-}
-
-impl<T: Copy> ToFixedSizedArray for Vec<T> {
-    type Item = T;
-    type Error = Self;
-    fn to_fixed_sized_array<const SIZE: usize>(self) -> Result<[Self::Item; SIZE], Self::Error> {
-        if self.capacity() == SIZE {
-            Ok(unsafe { *self.as_ptr().cast() })
-        } else {
-            Err(self)
+/// Reads lines of `word frequency` into weights, smoothed so that unlisted words are
+/// still possible.
+fn read_frequencies(path: impl AsRef<Path>) -> std::io::Result<HashMap<String, f64>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut weights = HashMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        if let (Some(word), Some(frequency)) = (parts.next(), parts.next()) {
+            if let Ok(frequency) = frequency.parse::<f64>() {
+                weights.insert(word.to_lowercase(), 1.0 + frequency);
+            }
         }
     }
+    Ok(weights)
 }
@@ -1,3 +1,4 @@
+pub mod strategy;
 pub mod wordle;
 
 pub trait Solver {
@@ -12,11 +13,17 @@ pub trait Solver {
         Self: 'a;
 
     type Constraint;
+    type ConstraintError;
     type Guess<'a>
     where
         Self: 'a;
 
     fn iter_possible_guesses<'a>(&'a self) -> Self::PossibleGuessesIterator<'a>;
     fn iter_best_next_guess<'a>(&'a self) -> Self::BestNextGuessIterator<'a>;
-    fn add_constraint(&mut self, constraint: Self::Constraint);
+    fn add_constraint(&mut self, constraint: Self::Constraint)
+        -> Result<(), Self::ConstraintError>;
+
+    fn possible_guess_count(&self) -> usize {
+        self.iter_possible_guesses().count()
+    }
 }
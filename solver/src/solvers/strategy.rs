@@ -0,0 +1,310 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::wordle::fixed_size_wordle_solver::{feedback_into, WordleCharStatus};
+use super::wordle::frequencies::Frequencies;
+use super::wordle::optimal::OptimalSearch;
+use super::wordle::pattern_matrix::PatternMatrix;
+use super::wordle::ranking::{rank_guesses, GuessMetric, Ranking};
+
+/// Everything a strategy may look at to rank the next guess. Words are referred to by
+/// their index in `words`, the possible answers being the first ones.
+pub struct RankingState<'a> {
+    /// Feedback of every guess against every answer, built on the first call.
+    pub patterns: &'a dyn Fn() -> &'a PatternMatrix,
+    /// Every word the game accepts as a guess.
+    pub words: &'a [String],
+    /// Guesses to rank.
+    pub guesses: &'a [usize],
+    /// Words that may still be the answer.
+    pub candidates: &'a [usize],
+    /// Whether a word may still be the answer, by word index.
+    pub is_candidate: &'a [bool],
+    /// Relative likelihood of every possible answer.
+    pub weights: &'a [f64],
+    pub remaining_attempts: usize,
+}
+
+impl<'a> RankingState<'a> {
+    pub fn matrix(&self) -> &'a PatternMatrix {
+        (self.patterns)()
+    }
+}
+
+/// Way of choosing the next guess, selectable at runtime.
+pub trait GuessRanker: Send + Sync {
+    /// Short unique name, e.g. for a command line option.
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Scores the guesses of `state` worth playing, best first. Cheap strategies may leave
+    /// out the guesses they consider poor.
+    fn rank(&self, state: &RankingState) -> Vec<(usize, GuessMetric)>;
+}
+
+impl GuessRanker for Ranking {
+    fn name(&self) -> &str {
+        match self {
+            Ranking::Entropy => "entropy",
+            Ranking::Minimax => "minimax",
+            Ranking::ExpectedRemaining => "expected-remaining",
+            Ranking::Lookahead { .. } => "lookahead",
+            Ranking::ExpectedGuesses => "expected-guesses",
+            Ranking::WinProbability => "win-probability",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            Ranking::Entropy => "most information on average",
+            Ranking::Minimax => "fewest candidates left in the worst case",
+            Ranking::ExpectedRemaining => "fewest candidates left on average",
+            Ranking::Lookahead { .. } => "fewest candidates left on average after two guesses",
+            Ranking::ExpectedGuesses => "fewest guesses to finish on average, estimated",
+            Ranking::WinProbability => "best chance to win within the attempts left, estimated",
+        }
+    }
+
+    fn rank(&self, state: &RankingState) -> Vec<(usize, GuessMetric)> {
+        rank_guesses(
+            state.matrix(),
+            state.guesses,
+            state.candidates,
+            state.is_candidate,
+            state.weights,
+            *self,
+            state.remaining_attempts,
+        )
+    }
+}
+
+/// Ranks by information, but plays the guess of the optimal decision tree once there are
+/// few enough candidates to search it exhaustively. The tree starts with a candidate or
+/// one of the `top_k` most informative guesses.
+pub struct OptimalEndgame {
+    pub max_candidates: usize,
+    pub top_k: usize,
+}
+
+impl GuessRanker for OptimalEndgame {
+    fn name(&self) -> &str {
+        "optimal-endgame"
+    }
+
+    fn description(&self) -> &str {
+        "most information, fewest guesses on average near the end"
+    }
+
+    fn rank(&self, state: &RankingState) -> Vec<(usize, GuessMetric)> {
+        let mut ranked = Ranking::Entropy.rank(state);
+        if state.candidates.len() > self.max_candidates {
+            return ranked;
+        }
+        let tree =
+            OptimalSearch::pruned(state.matrix(), state.guesses, state.candidates, self.top_k)
+                .solve(state.candidates, state.remaining_attempts);
+        if let Some(tree) = tree {
+            if let Some(best) = ranked.iter().position(|(guess, _)| *guess == tree.guess) {
+                let best = ranked.remove(best);
+                ranked.insert(0, best);
+            }
+        }
+        ranked
+    }
+}
+
+/// Ranks guesses by how much their tiles tell about the candidates one at a time, see
+/// [`Frequencies::score`]. Needs neither the pattern matrix nor a pass over every pair of
+/// guess and candidate, so it stays fast on large corpora; only the `top_k` best guesses
+/// are returned, scored against the candidates like the other strategies do.
+pub struct FrequencyHeuristic {
+    pub top_k: usize,
+}
+
+impl GuessRanker for FrequencyHeuristic {
+    fn name(&self) -> &str {
+        "frequency"
+    }
+
+    fn description(&self) -> &str {
+        "common letters at common positions, fast"
+    }
+
+    fn rank(&self, state: &RankingState) -> Vec<(usize, GuessMetric)> {
+        let word_length = state.words.first().map_or(0, |word| word.chars().count());
+        let candidates = state
+            .candidates
+            .iter()
+            .map(|&candidate| (state.words[candidate].as_str(), state.weights[candidate]));
+        let frequencies = Frequencies::new(word_length, candidates);
+        let mut scored: Vec<(usize, f64)> = state
+            .guesses
+            .iter()
+            .map(|&guess| (guess, frequencies.score(&state.words[guess])))
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| state.is_candidate[*b].cmp(&state.is_candidate[*a]))
+        });
+        scored.truncate(self.top_k);
+
+        let spell = |word: &String| word.chars().collect::<Vec<char>>();
+        let answers: Vec<Vec<char>> = state
+            .candidates
+            .iter()
+            .map(|&candidate| spell(&state.words[candidate]))
+            .collect();
+        scored
+            .into_iter()
+            .map(|(guess, score)| {
+                let guess_chars = spell(&state.words[guess]);
+                let mut groups: HashMap<Vec<WordleCharStatus>, (usize, f64)> = HashMap::new();
+                for (answer, &candidate) in answers.iter().zip(state.candidates) {
+                    let mut statuses = vec![WordleCharStatus::Inexistent; word_length];
+                    feedback_into(&guess_chars, answer, &mut statuses);
+                    let group = groups.entry(statuses).or_default();
+                    group.0 += 1;
+                    group.1 += state.weights[candidate];
+                }
+                let mut metric = GuessMetric::from_buckets(
+                    groups.into_values(),
+                    frequencies.total(),
+                    state.is_candidate[guess],
+                );
+                metric.letter_score = Some(score);
+                (guess, metric)
+            })
+            .collect()
+    }
+}
+
+/// Strategies by name.
+pub struct StrategyRegistry {
+    strategies: Vec<Arc<dyn GuessRanker>>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self { strategies: vec![] }
+    }
+
+    /// Registry of every strategy of this crate, the default one first.
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(Ranking::Entropy));
+        registry.register(Arc::new(Ranking::Minimax));
+        registry.register(Arc::new(Ranking::ExpectedRemaining));
+        registry.register(Arc::new(Ranking::Lookahead { top_k: 10 }));
+        registry.register(Arc::new(OptimalEndgame {
+            max_candidates: 30,
+            top_k: 30,
+        }));
+        registry.register(Arc::new(FrequencyHeuristic { top_k: 30 }));
+        registry.register(Arc::new(Ranking::ExpectedGuesses));
+        registry.register(Arc::new(Ranking::WinProbability));
+        registry
+    }
+
+    /// Adds `strategy`, replacing the one with the same name.
+    pub fn register(&mut self, strategy: Arc<dyn GuessRanker>) {
+        self.strategies
+            .retain(|known| known.name() != strategy.name());
+        self.strategies.push(strategy);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn GuessRanker>> {
+        self.strategies
+            .iter()
+            .find(|strategy| strategy.name() == name)
+            .cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn GuessRanker>> {
+        self.strategies.iter()
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::with_builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn registry_finds_strategies_by_name() {
+        let registry = StrategyRegistry::with_builtin();
+        let names: Vec<&str> = registry.iter().map(|strategy| strategy.name()).collect();
+        assert_eq!(
+            vec![
+                "entropy",
+                "minimax",
+                "expected-remaining",
+                "lookahead",
+                "optimal-endgame",
+                "frequency",
+                "expected-guesses",
+                "win-probability"
+            ],
+            names
+        );
+        assert_eq!("minimax", registry.get("minimax").unwrap().name());
+        assert!(registry.get("random").is_none());
+    }
+
+    #[test]
+    fn optimal_endgame_plays_the_tree_guess_first() {
+        let words = words(&["aab", "aac", "aad", "aae", "bcd", "xyz", "eda"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let state = RankingState {
+            patterns: &|| &matrix,
+            words: &words,
+            guesses: &all,
+            candidates: &all,
+            is_candidate: &[true; 7],
+            weights: &[1.0; 7],
+            remaining_attempts: 3,
+        };
+
+        let ranked = OptimalEndgame {
+            max_candidates: 30,
+            top_k: 30,
+        }
+        .rank(&state);
+        let tree = OptimalSearch::new(&matrix, all.clone())
+            .solve(&all, 3)
+            .unwrap();
+        assert_eq!(tree.guess, ranked[0].0);
+        assert_eq!(words.len(), ranked.len());
+    }
+
+    #[test]
+    fn frequency_heuristic_does_not_need_patterns() {
+        let words = words(&["abc", "abd", "abe", "abf", "def"]);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let state = RankingState {
+            patterns: &|| panic!("pattern matrix was built"),
+            words: &words,
+            guesses: &all,
+            candidates: &all[..4],
+            is_candidate: &[true, true, true, true, false],
+            weights: &[1.0; 4],
+            remaining_attempts: 6,
+        };
+
+        let ranked = FrequencyHeuristic { top_k: 3 }.rank(&state);
+        assert_eq!(3, ranked.len());
+        // tells the candidates apart though it can not be the answer
+        assert_eq!(4, ranked[0].0);
+        assert_eq!(4, ranked[0].1.buckets);
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].1.letter_score >= pair[1].1.letter_score));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+
+use super::fixed_size_wordle_solver::to_chars;
+
+/// Set of letters, bit `i` standing for the `i`-th letter of a corpus alphabet.
+pub type LetterMask = u64;
+
+pub const MAX_ALPHABET_SIZE: usize = LetterMask::BITS as usize;
+
+/// Word spelled with alphabet indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodedWord<const WORD_LENGTH: usize> {
+    pub letters: [u8; WORD_LENGTH],
+    /// `counts[k]` holds the letters occurring more than `k` times.
+    pub counts: [LetterMask; WORD_LENGTH],
+}
+
+impl<const WORD_LENGTH: usize> EncodedWord<WORD_LENGTH> {
+    pub fn new(letters: [u8; WORD_LENGTH]) -> Self {
+        let mut counts = [0; WORD_LENGTH];
+        for letter in letters {
+            let bit: LetterMask = 1 << letter;
+            // the first count level without the letter yet
+            if let Some(level) = counts.iter_mut().find(|level| **level & bit == 0) {
+                *level |= bit;
+            }
+        }
+        Self { letters, counts }
+    }
+
+    /// How many times `letter` occurs.
+    pub fn count(&self, letter: u8) -> usize {
+        let bit: LetterMask = 1 << letter;
+        self.counts
+            .iter()
+            .take_while(|level| **level & bit != 0)
+            .count()
+    }
+}
+
+/// Word lists of a game, encoded once and meant to be shared between sessions.
+///
+/// Words that do not have `WORD_LENGTH` letters are left out, and so are words that would
+/// grow the alphabet past [`MAX_ALPHABET_SIZE`] letters.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Corpus<const WORD_LENGTH: usize> {
+    alphabet: Vec<char>,
+    /// Every allowed guess, starting with the possible answers.
+    words: Vec<String>,
+    encoded: Vec<EncodedWord<WORD_LENGTH>>,
+    answers: usize,
+}
+
+impl<const WORD_LENGTH: usize> Corpus<WORD_LENGTH> {
+    pub fn new(answers: Vec<String>) -> Self {
+        Self::with_allowed_guesses(answers, vec![])
+    }
+
+    /// Corpus of a game whose answer is one of `answers`, but which also accepts any of
+    /// `allowed_guesses` as a guess.
+    pub fn with_allowed_guesses(answers: Vec<String>, allowed_guesses: Vec<String>) -> Self {
+        let mut corpus = Self {
+            alphabet: vec![],
+            words: vec![],
+            encoded: vec![],
+            answers: 0,
+        };
+        let mut letters = HashMap::new();
+        let mut seen = HashSet::new();
+        for word in answers {
+            if seen.insert(word.clone()) && corpus.push(word, &mut letters) {
+                corpus.answers += 1;
+            }
+        }
+        for word in allowed_guesses {
+            if seen.insert(word.clone()) {
+                corpus.push(word, &mut letters);
+            }
+        }
+        corpus
+    }
+
+    fn push(&mut self, word: String, letters: &mut HashMap<char, u8>) -> bool {
+        let Some(chars) = to_chars::<WORD_LENGTH>(&word) else {
+            return false;
+        };
+        let new_letters: HashSet<char> = chars
+            .iter()
+            .filter(|c| !letters.contains_key(c))
+            .copied()
+            .collect();
+        if self.alphabet.len() + new_letters.len() > MAX_ALPHABET_SIZE {
+            return false;
+        }
+        let encoded = chars.map(|c| {
+            *letters.entry(c).or_insert_with(|| {
+                self.alphabet.push(c);
+                (self.alphabet.len() - 1) as u8
+            })
+        });
+        self.encoded.push(EncodedWord::new(encoded));
+        self.words.push(word);
+        true
+    }
+
+    /// Words that may be the answer.
+    pub fn answers(&self) -> &[String] {
+        &self.words[..self.answers]
+    }
+
+    /// Every allowed guess, starting with the [`answers`](Self::answers).
+    pub fn guesses(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn word(&self, index: usize) -> &str {
+        &self.words[index]
+    }
+
+    pub fn encoded(&self, index: usize) -> &EncodedWord<WORD_LENGTH> {
+        &self.encoded[index]
+    }
+
+    pub fn position(&self, word: &str) -> Option<usize> {
+        self.words.iter().position(|w| w == word)
+    }
+
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    pub fn letter_index(&self, letter: char) -> Option<u8> {
+        self.alphabet
+            .iter()
+            .position(|c| *c == letter)
+            .map(|index| index as u8)
+    }
+
+    /// Encodes any word spelled with the corpus alphabet.
+    pub fn encode(&self, word: &str) -> Option<EncodedWord<WORD_LENGTH>> {
+        let chars = to_chars::<WORD_LENGTH>(word)?;
+        let mut letters = [0; WORD_LENGTH];
+        for (letter, c) in letters.iter_mut().zip(chars) {
+            *letter = self.letter_index(c)?;
+        }
+        Some(EncodedWord::new(letters))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn words_are_encoded_with_letter_counts() {
+        let answers = words(&["отток", "театр", "отток", "ток"]);
+        let corpus: Corpus<5> = Corpus::with_allowed_guesses(answers, words(&["театр"]));
+
+        assert_eq!(["отток", "театр"], corpus.answers());
+        assert_eq!(2, corpus.guesses().len());
+        assert_eq!(['о', 'т', 'к', 'е', 'а', 'р'], corpus.alphabet());
+
+        let word = corpus.encoded(0);
+        assert_eq!([0, 1, 1, 0, 2], word.letters);
+        assert_eq!(2, word.count(0));
+        assert_eq!(2, word.count(1));
+        assert_eq!(1, word.count(2));
+        assert_eq!(0, word.count(3));
+        assert_eq!(Some(*corpus.encoded(1)), corpus.encode("театр"));
+        assert_eq!(None, corpus.encode("ведро"));
+    }
+}
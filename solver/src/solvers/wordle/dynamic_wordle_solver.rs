@@ -1,38 +1,490 @@
+use std::{collections::HashMap, fmt::Display, io, path::PathBuf, sync::Arc};
+
+use super::super::strategy::GuessRanker;
 use super::super::Solver;
+use super::corpus::Corpus;
+use super::fixed_size_wordle_solver::{
+    AddGuessErrorVariant, BestNextGuessIterator, Contradiction, Elimination, ExcludeWordError,
+    NaiveSolver, WordleCharStatus, WordleLine,
+};
+use super::frequencies::Frequencies;
+use super::optimal::DecisionTree;
+use super::ranking::GuessMetric;
+
+pub const MIN_WORD_LENGTH: usize = 2;
+pub const MAX_WORD_LENGTH: usize = super::pattern_matrix::MAX_WORD_LENGTH;
+
+/// Wordle solver for a word length only known at runtime.
+///
+/// Wraps a [`NaiveSolver`] of the matching length, so it behaves exactly the same.
+pub struct WordleSolver {
+    inner: Inner,
+}
+
+enum Inner {
+    L2(NaiveSolver<2>),
+    L3(NaiveSolver<3>),
+    L4(NaiveSolver<4>),
+    L5(NaiveSolver<5>),
+    L6(NaiveSolver<6>),
+    L7(NaiveSolver<7>),
+    L8(NaiveSolver<8>),
+    L9(NaiveSolver<9>),
+    L10(NaiveSolver<10>),
+}
+
+macro_rules! dispatch {
+    ($enum:ident, $inner:expr, $solver:ident => $body:expr) => {
+        match $inner {
+            $enum::L2($solver) => $body,
+            $enum::L3($solver) => $body,
+            $enum::L4($solver) => $body,
+            $enum::L5($solver) => $body,
+            $enum::L6($solver) => $body,
+            $enum::L7($solver) => $body,
+            $enum::L8($solver) => $body,
+            $enum::L9($solver) => $body,
+            $enum::L10($solver) => $body,
+        }
+    };
+    ($inner:expr, $solver:ident => $body:expr) => {
+        dispatch!(Inner, $inner, $solver => $body)
+    };
+}
+
+impl WordleSolver {
+    pub fn new(word_length: usize, corpus: Vec<String>) -> Result<Self, UnsupportedWordLength> {
+        Self::with_allowed_guesses(word_length, corpus, vec![])
+    }
+
+    /// Solver for the length most of the corpus words have.
+    pub fn from_corpus(corpus: Vec<String>) -> Result<Self, UnsupportedWordLength> {
+        let word_length = detect_word_length(&corpus).unwrap_or(0);
+        Self::new(word_length, corpus)
+    }
+
+    pub fn with_allowed_guesses(
+        word_length: usize,
+        corpus: Vec<String>,
+        allowed_guesses: Vec<String>,
+    ) -> Result<Self, UnsupportedWordLength> {
+        let corpus = DynamicCorpus::with_allowed_guesses(word_length, corpus, allowed_guesses)?;
+        Ok(Self::from_shared_corpus(&corpus))
+    }
+
+    /// New session over a corpus that may be shared with other sessions.
+    pub fn from_shared_corpus(corpus: &DynamicCorpus) -> Self {
+        let inner = match &corpus.inner {
+            CorpusInner::L2(c) => Inner::L2(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L3(c) => Inner::L3(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L4(c) => Inner::L4(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L5(c) => Inner::L5(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L6(c) => Inner::L6(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L7(c) => Inner::L7(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L8(c) => Inner::L8(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L9(c) => Inner::L9(NaiveSolver::from_corpus(c.clone())),
+            CorpusInner::L10(c) => Inner::L10(NaiveSolver::from_corpus(c.clone())),
+        };
+        Self { inner }
+    }
+
+    pub fn word_length(&self) -> usize {
+        dispatch!(&self.inner, solver => word_length_of(solver))
+    }
+
+    pub fn use_pattern_cache(&mut self, path: impl Into<PathBuf>) {
+        dispatch!(&mut self.inner, solver => solver.use_pattern_cache(path))
+    }
+
+    pub fn take_pattern_cache_error(&mut self) -> Option<io::Error> {
+        dispatch!(&mut self.inner, solver => solver.take_pattern_cache_error())
+    }
+
+    pub fn set_weights(&mut self, weights: &HashMap<String, f64>, missing_weight: f64) {
+        dispatch!(&mut self.inner, solver => solver.set_weights(weights, missing_weight))
+    }
+
+    pub fn weight(&self, word: &str) -> Option<f64> {
+        dispatch!(&self.inner, solver => solver.weight(word))
+    }
+
+    pub fn iter_corpus(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        dispatch!(&self.inner, solver => Box::new(solver.iter_corpus()))
+    }
+
+    pub fn candidate_count(&self) -> usize {
+        dispatch!(&self.inner, solver => solver.candidate_count())
+    }
+
+    pub fn letter_frequencies(&self) -> Frequencies {
+        dispatch!(&self.inner, solver => solver.letter_frequencies())
+    }
+
+    pub fn add_guess(&mut self, guess: DynamicWordleLine) -> Result<(), AddGuessError> {
+        let word_length = self.word_length();
+        dispatch!(&mut self.inner, solver => match guess.try_into() {
+            Ok(line) => solver.add_guess(line).map_err(AddGuessError::from),
+            Err(guess) => Err(AddGuessError {
+                guess,
+                error: AddGuessErrorVariant::WordLength {
+                    expected_length: word_length,
+                },
+            }),
+        })
+    }
 
-pub struct WordleSolver<'corpus>
-where
-    Self: 'corpus,
-{
-    corpus: &'corpus [&'corpus str],
+    pub fn pop_guess(&mut self) -> Option<DynamicWordleLine> {
+        dispatch!(&mut self.inner, solver => solver.pop_guess().map(DynamicWordleLine::from))
+    }
+
+    pub fn redo(&mut self) -> Option<Result<DynamicWordleLine, AddGuessError>> {
+        dispatch!(&mut self.inner, solver => {
+            let redone = solver.redo()?;
+            Some(redone.cloned().map(DynamicWordleLine::from).map_err(AddGuessError::from))
+        })
+    }
+
+    pub fn history(&self) -> Vec<DynamicWordleLine> {
+        dispatch!(&self.inner, solver => {
+            solver.history().iter().cloned().map(DynamicWordleLine::from).collect()
+        })
+    }
+
+    /// See [`NaiveSolver::replace_guess`].
+    pub fn replace_guess(
+        &mut self,
+        index: usize,
+        guess: DynamicWordleLine,
+    ) -> Option<Result<DynamicWordleLine, AddGuessError>> {
+        let word_length = self.word_length();
+        dispatch!(&mut self.inner, solver => match guess.try_into() {
+            Ok(line) => {
+                let replaced = solver.replace_guess(index, line)?;
+                Some(replaced.map(DynamicWordleLine::from).map_err(AddGuessError::from))
+            }
+            Err(guess) => Some(Err(AddGuessError {
+                guess,
+                error: AddGuessErrorVariant::WordLength {
+                    expected_length: word_length,
+                },
+            })),
+        })
+    }
+
+    pub fn explain(&self, word: &str) -> Option<Elimination> {
+        dispatch!(&self.inner, solver => solver.explain(word))
+    }
+
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        dispatch!(&self.inner, solver => solver.contradiction())
+    }
+
+    pub fn hard_mode(&self) -> bool {
+        dispatch!(&self.inner, solver => solver.hard_mode())
+    }
+
+    pub fn set_hard_mode(&mut self, hard_mode: bool) {
+        dispatch!(&mut self.inner, solver => solver.set_hard_mode(hard_mode))
+    }
+
+    pub fn is_excluded(&self, word: &str) -> bool {
+        dispatch!(&self.inner, solver => solver.is_excluded(word))
+    }
+
+    pub fn exclude(&mut self, word: String) -> Result<(), ExcludeWordError> {
+        dispatch!(&mut self.inner, solver => solver.exclude(word))
+    }
+
+    pub fn performed_guesses(&self) -> usize {
+        dispatch!(&self.inner, solver => solver.performed_guesses())
+    }
+
+    pub fn max_attempts(&self) -> usize {
+        dispatch!(&self.inner, solver => solver.max_attempts())
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: usize) {
+        dispatch!(&mut self.inner, solver => solver.set_max_attempts(max_attempts))
+    }
+
+    pub fn remaining_attempts(&self) -> usize {
+        dispatch!(&self.inner, solver => solver.remaining_attempts())
+    }
+
+    pub fn strategy(&self) -> &Arc<dyn GuessRanker> {
+        dispatch!(&self.inner, solver => solver.strategy())
+    }
+
+    pub fn set_strategy(&mut self, strategy: Arc<dyn GuessRanker>) {
+        dispatch!(&mut self.inner, solver => solver.set_strategy(strategy))
+    }
+
+    pub fn optimal_decision_tree(&self, max_depth: usize) -> Option<DecisionTree<&str>> {
+        dispatch!(&self.inner, solver => solver.optimal_decision_tree(max_depth))
+    }
 }
 
-impl<'corpus> Solver for WordleSolver<'corpus> {
-    type PossibleGuessesIterator<'a>
+fn word_length_of<const WORD_LENGTH: usize>(_: &NaiveSolver<WORD_LENGTH>) -> usize {
+    WORD_LENGTH
+}
+
+/// [`Corpus`] for a word length only known at runtime, cheap to clone and share between
+/// [`WordleSolver`] sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicCorpus {
+    inner: CorpusInner,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CorpusInner {
+    L2(Arc<Corpus<2>>),
+    L3(Arc<Corpus<3>>),
+    L4(Arc<Corpus<4>>),
+    L5(Arc<Corpus<5>>),
+    L6(Arc<Corpus<6>>),
+    L7(Arc<Corpus<7>>),
+    L8(Arc<Corpus<8>>),
+    L9(Arc<Corpus<9>>),
+    L10(Arc<Corpus<10>>),
+}
+
+impl DynamicCorpus {
+    /// Corpus of the `answers` that have `word_length` letters.
+    pub fn new(word_length: usize, answers: Vec<String>) -> Result<Self, UnsupportedWordLength> {
+        Self::with_allowed_guesses(word_length, answers, vec![])
+    }
+
+    pub fn with_allowed_guesses(
+        word_length: usize,
+        answers: Vec<String>,
+        allowed_guesses: Vec<String>,
+    ) -> Result<Self, UnsupportedWordLength> {
+        let (c, a) = (answers, allowed_guesses);
+        let inner = match word_length {
+            2 => CorpusInner::L2(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            3 => CorpusInner::L3(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            4 => CorpusInner::L4(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            5 => CorpusInner::L5(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            6 => CorpusInner::L6(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            7 => CorpusInner::L7(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            8 => CorpusInner::L8(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            9 => CorpusInner::L9(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            10 => CorpusInner::L10(Arc::new(Corpus::with_allowed_guesses(c, a))),
+            _ => return Err(UnsupportedWordLength { word_length }),
+        };
+        Ok(Self { inner })
+    }
+
+    pub fn word_length(&self) -> usize {
+        dispatch!(CorpusInner, &self.inner, corpus => corpus_word_length(corpus))
+    }
+
+    pub fn answers(&self) -> &[String] {
+        dispatch!(CorpusInner, &self.inner, corpus => corpus.answers())
+    }
+}
+
+fn corpus_word_length<const WORD_LENGTH: usize>(_: &Corpus<WORD_LENGTH>) -> usize {
+    WORD_LENGTH
+}
+
+/// The most common word length in `corpus`, preferring the shorter one on a tie.
+pub fn detect_word_length(corpus: &[String]) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for word in corpus {
+        *counts.entry(word.chars().count()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(length, _)| (MIN_WORD_LENGTH..=MAX_WORD_LENGTH).contains(length))
+        .max_by(|(a_length, a_count), (b_length, b_count)| {
+            a_count.cmp(b_count).then(b_length.cmp(a_length))
+        })
+        .map(|(length, _)| length)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnsupportedWordLength {
+    pub word_length: usize,
+}
+
+impl std::error::Error for UnsupportedWordLength {}
+
+impl Display for UnsupportedWordLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Words of {} letters are not supported, expected {} to {}",
+            self.word_length, MIN_WORD_LENGTH, MAX_WORD_LENGTH
+        )
+    }
+}
+
+/// [`WordleLine`] of any length.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DynamicWordleLine {
+    pub chars: Vec<(char, WordleCharStatus)>,
+}
+
+impl DynamicWordleLine {
+    pub fn new(letters: Vec<(char, WordleCharStatus)>) -> Self {
+        Self { chars: letters }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn word(&self) -> String {
+        self.chars.iter().map(|(character, _)| character).collect()
+    }
+}
+
+impl<const WORD_LENGTH: usize> From<WordleLine<WORD_LENGTH>> for DynamicWordleLine {
+    fn from(line: WordleLine<WORD_LENGTH>) -> Self {
+        Self::new(line.chars.to_vec())
+    }
+}
+
+impl<const WORD_LENGTH: usize> TryFrom<DynamicWordleLine> for WordleLine<WORD_LENGTH> {
+    type Error = DynamicWordleLine;
+
+    fn try_from(line: DynamicWordleLine) -> Result<Self, Self::Error> {
+        match line.chars.try_into() {
+            Ok(chars) => Ok(WordleLine::new(chars)),
+            Err(chars) => Err(DynamicWordleLine::new(chars)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AddGuessError {
+    pub guess: DynamicWordleLine,
+    pub error: AddGuessErrorVariant,
+}
+
+impl<const WL: usize> From<super::fixed_size_wordle_solver::AddGuessError<WL>> for AddGuessError {
+    fn from(error: super::fixed_size_wordle_solver::AddGuessError<WL>) -> Self {
+        Self {
+            guess: error.guess.into(),
+            error: error.error,
+        }
+    }
+}
+
+impl Display for AddGuessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error {
+            AddGuessErrorVariant::WordLength { expected_length } => {
+                write!(f, "Invalid guess length, expected {}", expected_length)
+            }
+            AddGuessErrorVariant::NoMoreAttempts { total_attempts: _ } => {
+                write!(f, "No more attempts")
+            }
+            AddGuessErrorVariant::HardMode { violation } => {
+                write!(f, "Hard mode: {}", violation)
+            }
+            AddGuessErrorVariant::BreaksLaterGuess { line, violation } => {
+                write!(f, "Hard mode: in guess {}, {}", line + 1, violation)
+            }
+        }
+    }
+}
+
+impl Solver for WordleSolver {
+    type PossibleGuessesIterator<'a> = Box<dyn Iterator<Item = &'a str> + 'a>
     where
         Self: 'a;
 
-    type GuessMetric;
+    type GuessMetric = GuessMetric;
 
-    type BestNextGuessIterator<'a>
+    type BestNextGuessIterator<'a> = BestNextGuessIterator<'a>
     where
         Self: 'a;
 
-    type Constraint;
+    type Constraint = DynamicWordleLine;
+
+    type ConstraintError = AddGuessError;
 
     type Guess<'a> = &'a str
     where
         Self: 'a;
 
     fn iter_possible_guesses<'a>(&'a self) -> Self::PossibleGuessesIterator<'a> {
-        todo!()
+        self.iter_corpus()
     }
 
     fn iter_best_next_guess<'a>(&'a self) -> Self::BestNextGuessIterator<'a> {
-        todo!()
+        dispatch!(&self.inner, solver => solver.iter_best_next_guess())
+    }
+
+    fn add_constraint(
+        &mut self,
+        constraint: Self::Constraint,
+    ) -> Result<(), Self::ConstraintError> {
+        self.add_guess(constraint)
+    }
+
+    fn possible_guess_count(&self) -> usize {
+        self.candidate_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn solver_uses_word_length_of_corpus() {
+        use WordleCharStatus::*;
+
+        let corpus = words(&["ночь", "дочь", "мышь", "река", "цветок"]);
+        let mut game = WordleSolver::from_corpus(corpus).unwrap();
+        assert_eq!(4, game.word_length());
+
+        let guess = DynamicWordleLine::new(vec![
+            ('д', Inexistent),
+            ('о', Good),
+            ('ч', Good),
+            ('ь', Good),
+        ]);
+        assert_eq!(Ok(()), game.add_guess(guess));
+        assert_eq!(vec!["ночь"], game.iter_corpus().collect::<Vec<_>>());
+
+        let too_long = DynamicWordleLine::new(vec![('н', Good); 5]);
+        assert_eq!(
+            AddGuessErrorVariant::WordLength { expected_length: 4 },
+            game.add_guess(too_long).unwrap_err().error
+        );
+        assert_eq!(
+            Err(UnsupportedWordLength { word_length: 1 }),
+            WordleSolver::new(1, vec![]).map(|_| ())
+        );
     }
 
-    fn add_constraint(&mut self, constraint: Self::Constraint) {
-        todo!()
+    #[test]
+    fn sessions_share_corpus() {
+        use WordleCharStatus::*;
+
+        let corpus = DynamicCorpus::new(4, words(&["ночь", "дочь", "цветок"])).unwrap();
+        assert_eq!(4, corpus.word_length());
+        assert_eq!(words(&["ночь", "дочь"]), corpus.answers());
+
+        let mut first = WordleSolver::from_shared_corpus(&corpus);
+        let second = WordleSolver::from_shared_corpus(&corpus);
+        let guess = DynamicWordleLine::new(vec![
+            ('д', Inexistent),
+            ('о', Good),
+            ('ч', Good),
+            ('ь', Good),
+        ]);
+        assert_eq!(Ok(()), first.add_guess(guess));
+        assert_eq!(1, first.candidate_count());
+        assert_eq!(2, second.candidate_count());
     }
 }
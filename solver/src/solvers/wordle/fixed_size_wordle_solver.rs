@@ -1,73 +1,387 @@
 use std::{
     collections::HashMap,
     fmt::Display,
-    iter::{Map, Take},
+    io,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
     // ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign},
 };
 
+use super::super::strategy::{GuessRanker, RankingState};
 use super::super::Solver;
+use super::corpus::Corpus;
+use super::frequencies::Frequencies;
+use super::line_mask::LineMask;
+use super::optimal::{DecisionTree, OptimalSearch};
+use super::pattern_matrix::PatternMatrix;
+use super::ranking::{GuessMetric, Ranking};
 
 pub struct NaiveSolver<const WORD_LENGTH: usize> {
     lines: Vec<WordleLine<WORD_LENGTH>>,
+    /// Guesses taken back with `pop_guess`, the latest last.
+    undone: Vec<WordleLine<WORD_LENGTH>>,
     performed_guesses: usize,
-    corpus: Vec<String>,
+    corpus: Arc<Corpus<WORD_LENGTH>>,
+    /// Relative likelihood of every corpus word being the answer.
+    weights: Vec<f64>,
+    /// Corpus indices from the most to the least likely answer.
+    order: Vec<usize>,
+    /// Corpus indices that may still be the answer, in the same order as `order`.
+    candidates: Vec<usize>,
     corpus_exclude: Vec<String>,
-    //hard_mode: bool,
+    patterns: OnceLock<PatternMatrix>,
+    /// File the pattern matrix is loaded from or saved to when it is first needed.
+    pattern_cache: Option<PathBuf>,
+    pattern_cache_error: OnceLock<io::Error>,
+    strategy: Arc<dyn GuessRanker>,
+    hard_mode: bool,
+    max_attempts: usize,
 }
 
+/// Attempts the original game gives to find the answer.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 6;
+
+/// Guesses besides the candidates an optimal decision tree may start with.
+const OPTIMAL_SEARCH_GUESSES: usize = 30;
+
 impl<const WORD_LENGTH: usize> NaiveSolver<WORD_LENGTH> {
     pub fn new(corpus: Vec<String>) -> Self {
+        Self::with_allowed_guesses(corpus, vec![])
+    }
+
+    /// Solver for a game whose answer is one of `corpus`, but which also accepts any of
+    /// `allowed_guesses` as a guess.
+    pub fn with_allowed_guesses(corpus: Vec<String>, allowed_guesses: Vec<String>) -> Self {
+        Self::from_corpus(Arc::new(Corpus::with_allowed_guesses(
+            corpus,
+            allowed_guesses,
+        )))
+    }
+
+    /// New session over a corpus that may be shared with other sessions.
+    pub fn from_corpus(corpus: Arc<Corpus<WORD_LENGTH>>) -> Self {
+        let answers = corpus.answers().len();
         Self {
+            weights: vec![1.0; answers],
+            order: (0..answers).collect(),
+            candidates: (0..answers).collect(),
             corpus,
             lines: vec![],
+            undone: vec![],
             performed_guesses: 0,
             corpus_exclude: vec![],
-            // hard_mode: false,
+            patterns: OnceLock::new(),
+            pattern_cache: None,
+            pattern_cache_error: OnceLock::new(),
+            strategy: Arc::new(Ranking::default()),
+            hard_mode: false,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
         }
     }
 
+    /// Feedback of every allowed guess against every corpus word, built on first use or
+    /// taken from the pattern cache, see [`Self::use_pattern_cache`].
+    pub fn pattern_matrix(&self) -> &PatternMatrix {
+        self.patterns.get_or_init(|| {
+            if let Some(path) = &self.pattern_cache {
+                match PatternMatrix::load_or_build(path, &self.corpus) {
+                    Ok(matrix) => return matrix,
+                    Err(e) => {
+                        let _ = self.pattern_cache_error.set(e);
+                    }
+                }
+            }
+            PatternMatrix::from_corpus(&self.corpus)
+        })
+    }
+
+    pub fn corpus(&self) -> &Arc<Corpus<WORD_LENGTH>> {
+        &self.corpus
+    }
+
+    /// Takes the pattern matrix from the cache file at `path` once it is needed, rebuilding
+    /// the file when it is missing or was made for other word lists. Strategies that do
+    /// without the matrix never touch the file.
+    pub fn use_pattern_cache(&mut self, path: impl Into<PathBuf>) {
+        self.pattern_cache = Some(path.into());
+    }
+
+    /// Why the pattern cache could not be used, if it could not. The matrix is built in
+    /// memory then.
+    pub fn take_pattern_cache_error(&mut self) -> Option<io::Error> {
+        self.pattern_cache_error.take()
+    }
+
+    /// Weighs corpus words by how likely they are to be the answer, e.g. by their
+    /// frequency. Words without a positive weight in `weights` get `missing_weight`.
+    pub fn set_weights(&mut self, weights: &HashMap<String, f64>, missing_weight: f64) {
+        assert!(missing_weight > 0.0, "weight must be positive");
+        self.weights = self
+            .corpus
+            .answers()
+            .iter()
+            .map(|word| match weights.get(word) {
+                Some(weight) if *weight > 0.0 && weight.is_finite() => *weight,
+                _ => missing_weight,
+            })
+            .collect();
+        self.order = (0..self.weights.len()).collect();
+        self.order
+            .sort_by(|a, b| self.weights[*b].total_cmp(&self.weights[*a]));
+        self.candidates.sort_unstable();
+        self.candidates
+            .sort_by(|a, b| self.weights[*b].total_cmp(&self.weights[*a]));
+    }
+
+    pub fn weight(&self, word: &str) -> Option<f64> {
+        let index = self.corpus.position(word)?;
+        self.weights.get(index).copied()
+    }
+
+    /// Words that may still be the answer, the most likely first.
     pub fn iter_corpus<'game>(&'game self) -> CorpusIterator<'game, WORD_LENGTH> {
         CorpusIterator::new(self)
     }
 
+    /// Number of words that may still be the answer.
+    pub fn candidate_count(&self) -> usize {
+        self.candidates.len()
+    }
+
     pub fn add_guess(
         &mut self,
         guess: WordleLine<WORD_LENGTH>,
     ) -> Result<(), AddGuessError<WORD_LENGTH>> {
+        match self.new_guess_error(&guess) {
+            Some(error) => Err(AddGuessError { guess, error }),
+            None => {
+                self.undone.clear();
+                self.push_guess(guess);
+                Ok(())
+            }
+        }
+    }
+
+    /// Why `guess` may not be played next, if it may not.
+    fn new_guess_error(&self, guess: &WordleLine<WORD_LENGTH>) -> Option<AddGuessErrorVariant> {
         if guess.len() != WORD_LENGTH {
-            Err(AddGuessError {
-                guess,
-                error: AddGuessErrorVariant::WordLength {
-                    expected_length: WORD_LENGTH,
-                },
+            Some(AddGuessErrorVariant::WordLength {
+                expected_length: WORD_LENGTH,
+            })
+        } else if self.remaining_attempts() == 0 {
+            Some(AddGuessErrorVariant::NoMoreAttempts {
+                total_attempts: self.max_attempts,
             })
         } else {
-            self.lines.push(guess);
-            self.performed_guesses += 1;
-            Ok(())
+            self.hard_mode_violation_after(&self.lines, &guess.word())
+                .map(|violation| AddGuessErrorVariant::HardMode { violation })
         }
     }
 
-    // pub fn get_frequencies(&self) -> (usize, HashMap<char, Frequencies<WORD_LENGTH>>) {
-    //     let mut entropy = HashMap::with_capacity(50);
-    //     let mut total = 0;
-    //     for word in self.iter_corpus() {
-    //         for (i, character) in word.chars().take(WORD_LENGTH).enumerate() {
-    //             let entry: &mut Frequencies<WORD_LENGTH> = entropy.entry(character).or_default();
-    //             entry.positions[i] += 1;
-    //         }
-    //         total += 1;
-    //     }
-    //     (total, entropy)
-    // }
-
-    // fn calcualte_entropy(count: usize, frequencies: HashMap<char, Frequencies<WORD_LENGTH>>){
-    //     for (character, frequencies) in frequencies.iter() {
-    //         let mut exact = [0f32; WORD_LENGTH];
-    //         let mut another = [0f32; WORD_LENGTH];
-    //         let mut miss = 0f32;
-    //     }
-    // }
+    fn push_guess(&mut self, guess: WordleLine<WORD_LENGTH>) {
+        let mask = LineMask::compile(&guess, &self.corpus);
+        self.candidates
+            .retain(|&index| mask.matches(self.corpus.encoded(index)));
+        self.lines.push(guess);
+        self.performed_guesses += 1;
+    }
+
+    /// Takes back the latest guess, so that it can be redone.
+    pub fn pop_guess(&mut self) -> Option<WordleLine<WORD_LENGTH>> {
+        let guess = self.lines.pop()?;
+        self.performed_guesses -= 1;
+        self.undone.push(guess.clone());
+        self.refilter();
+        Some(guess)
+    }
+
+    /// Plays again the guess taken back last, unless another guess was added since. The
+    /// guess is checked like a new one and stays undone if it is rejected.
+    pub fn redo(&mut self) -> Option<Result<&WordleLine<WORD_LENGTH>, AddGuessError<WORD_LENGTH>>> {
+        let guess = self.undone.last()?;
+        if let Some(error) = self.new_guess_error(guess) {
+            let guess = guess.clone();
+            return Some(Err(AddGuessError { guess, error }));
+        }
+        let guess = self.undone.pop()?;
+        self.push_guess(guess);
+        self.lines.last().map(Ok)
+    }
+
+    /// Guesses played so far, the first one first.
+    pub fn history(&self) -> &[WordleLine<WORD_LENGTH>] {
+        &self.lines
+    }
+
+    /// Replaces the guess played at `index`, e.g. to fix a mistyped feedback, and returns
+    /// the replaced one. In hard mode `guess` has to reuse the hints of the guesses played
+    /// before it, and the guesses played after it have to reuse its hints. Returns `None`
+    /// if there is no such guess.
+    pub fn replace_guess(
+        &mut self,
+        index: usize,
+        guess: WordleLine<WORD_LENGTH>,
+    ) -> Option<Result<WordleLine<WORD_LENGTH>, AddGuessError<WORD_LENGTH>>> {
+        if index >= self.lines.len() {
+            return None;
+        }
+        if guess.len() != WORD_LENGTH {
+            let error = AddGuessErrorVariant::WordLength {
+                expected_length: WORD_LENGTH,
+            };
+            return Some(Err(AddGuessError { guess, error }));
+        }
+        let mut lines = self.lines.clone();
+        lines[index] = guess;
+        for line in index..lines.len() {
+            let earlier = &lines[..line];
+            let Some(violation) = self.hard_mode_violation_after(earlier, &lines[line].word())
+            else {
+                continue;
+            };
+            let error = if line == index {
+                AddGuessErrorVariant::HardMode { violation }
+            } else {
+                AddGuessErrorVariant::BreaksLaterGuess { line, violation }
+            };
+            let guess = lines[index].clone();
+            return Some(Err(AddGuessError { guess, error }));
+        }
+        let replaced = self.lines[index].clone();
+        self.lines = lines;
+        self.refilter();
+        Some(Ok(replaced))
+    }
+
+    fn refilter(&mut self) {
+        self.candidates = self.matching(&self.line_masks()).collect();
+    }
+
+    fn line_masks(&self) -> Vec<LineMask<WORD_LENGTH>> {
+        self.lines
+            .iter()
+            .map(|line| LineMask::compile(line, &self.corpus))
+            .collect()
+    }
+
+    /// Corpus indices of the words that are not excluded and match every mask, the most
+    /// likely first.
+    fn matching<'a>(
+        &'a self,
+        masks: &'a [LineMask<WORD_LENGTH>],
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.fitting(masks)
+            .filter(|&index| !self.is_excluded(self.corpus.word(index)))
+    }
+
+    /// Like [`Self::matching`], excluded words included.
+    fn fitting<'a>(
+        &'a self,
+        masks: &'a [LineMask<WORD_LENGTH>],
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.order.iter().copied().filter(|&index| {
+            let word = self.corpus.encoded(index);
+            masks.iter().all(|mask| mask.matches(word))
+        })
+    }
+
+    /// Explains why no word fits the guesses anymore, if that is the case. Words left out
+    /// with [`Self::exclude`] are not blamed on the guesses: if only they fit, there is no
+    /// contradiction.
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        if !self.candidates.is_empty() {
+            return None;
+        }
+        let mut masks = self.line_masks();
+        let line = (0..masks.len()).find(|&i| self.fitting(&masks[..=i]).next().is_none())?;
+
+        let statuses = [
+            WordleCharStatus::Inexistent,
+            WordleCharStatus::Existing,
+            WordleCharStatus::Good,
+        ];
+        let mut fixes = vec![];
+        for (index, original) in self.lines.iter().enumerate() {
+            for position in 0..WORD_LENGTH {
+                for status in statuses {
+                    if status == original.chars[position].1 {
+                        continue;
+                    }
+                    let mut fixed = original.clone();
+                    fixed.chars[position].1 = status;
+                    masks[index] = LineMask::compile(&fixed, &self.corpus);
+                    let candidates = self.fitting(&masks).count();
+                    if candidates > 0 {
+                        fixes.push(TileFix {
+                            line: index,
+                            position,
+                            status,
+                            candidates,
+                        });
+                    }
+                }
+            }
+            masks[index] = LineMask::compile(original, &self.corpus);
+        }
+        fixes.sort_by_key(|fix| fix.line != line);
+
+        Some(Contradiction { line, fixes })
+    }
+
+    pub fn hard_mode(&self) -> bool {
+        self.hard_mode
+    }
+
+    pub fn set_hard_mode(&mut self, hard_mode: bool) {
+        self.hard_mode = hard_mode;
+    }
+
+    /// The first revealed hint `word` does not reuse, if hard mode is on.
+    pub fn hard_mode_violation(&self, word: &str) -> Option<HardModeViolation> {
+        self.hard_mode_violation_after(&self.lines, word)
+    }
+
+    fn hard_mode_violation_after(
+        &self,
+        lines: &[WordleLine<WORD_LENGTH>],
+        word: &str,
+    ) -> Option<HardModeViolation> {
+        if !self.hard_mode {
+            return None;
+        }
+        for line in lines {
+            let tiles = line.chars.iter().zip(word.chars()).enumerate();
+            for (position, ((letter, status), character)) in tiles {
+                if *status == WordleCharStatus::Good && *letter != character {
+                    return Some(HardModeViolation::MissingGood {
+                        letter: *letter,
+                        position,
+                    });
+                }
+            }
+        }
+        for line in lines {
+            for (letter, status) in &line.chars {
+                if *status != WordleCharStatus::Existing {
+                    continue;
+                }
+                let (required, _) = line.letter_count_bounds(*letter);
+                if word.chars().filter(|c| c == letter).count() < required {
+                    return Some(HardModeViolation::MissingExisting { letter: *letter });
+                }
+            }
+        }
+        None
+    }
+
+    /// Letter frequencies of the words that may still be the answer.
+    pub fn letter_frequencies(&self) -> Frequencies {
+        let words = self
+            .candidates
+            .iter()
+            .map(|&candidate| (self.corpus.word(candidate), self.weights[candidate]));
+        Frequencies::new(WORD_LENGTH, words)
+    }
 
     pub fn is_excluded(&self, word: &str) -> bool {
         self.corpus_exclude.iter().any(|w| w == word)
@@ -76,7 +390,9 @@ impl<const WORD_LENGTH: usize> NaiveSolver<WORD_LENGTH> {
     pub fn exclude(&mut self, word: String) -> Result<(), ExcludeWordError> {
         if self.is_excluded(&word) {
             Err(ExcludeWordError::AlreadyExcluded)
-        } else if WORD_LENGTH == word.len() {
+        } else if WORD_LENGTH == word.chars().count() {
+            self.candidates
+                .retain(|&index| self.corpus.word(index) != word);
             self.corpus_exclude.push(word);
             Ok(())
         } else {
@@ -86,14 +402,85 @@ impl<const WORD_LENGTH: usize> NaiveSolver<WORD_LENGTH> {
         }
     }
 
+    /// Why `word` can not be the answer, or `None` if it still may be.
+    pub fn explain(&self, word: &str) -> Option<Elimination> {
+        let length = word.chars().count();
+        if length != WORD_LENGTH {
+            return Some(Elimination::WordLength {
+                expected_length: WORD_LENGTH,
+                length,
+            });
+        }
+        if !self.corpus.answers().iter().any(|answer| answer == word) {
+            return Some(Elimination::NotAnAnswer);
+        }
+        if self.is_excluded(word) {
+            return Some(Elimination::Excluded);
+        }
+        self.lines.iter().enumerate().find_map(|(line, guess)| {
+            let violation = guess.violation(word)?;
+            Some(Elimination::Guess {
+                line,
+                guess: guess.word(),
+                violation,
+            })
+        })
+    }
+
     pub fn performed_guesses(&self) -> usize {
         self.performed_guesses
     }
-}
 
-// pub struct Frequencies<const WORD_LENGTH: usize> {
-//     positions: [usize; WORD_LENGTH],
-// }
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Guesses already played stay played even if they exceed the new limit.
+    pub fn set_max_attempts(&mut self, max_attempts: usize) {
+        self.max_attempts = max_attempts;
+    }
+
+    pub fn remaining_attempts(&self) -> usize {
+        self.max_attempts.saturating_sub(self.performed_guesses)
+    }
+
+    /// Strategy ranking the next guesses.
+    pub fn strategy(&self) -> &Arc<dyn GuessRanker> {
+        &self.strategy
+    }
+
+    pub fn set_strategy(&mut self, strategy: Arc<dyn GuessRanker>) {
+        self.strategy = strategy;
+    }
+
+    /// Strategy with the fewest guesses on average that finds any remaining candidate in at
+    /// most `max_depth` guesses, trying the candidates and the most informative other
+    /// guesses. The search is exhaustive, so keep the candidate set small; it gives up and
+    /// returns `None` when it would take too long. Only the feedback on the candidates is
+    /// computed, not the whole pattern matrix.
+    pub fn optimal_decision_tree(&self, max_depth: usize) -> Option<DecisionTree<&str>> {
+        // candidates first, so that they are valid guess indices of the matrix too
+        let mut guesses = self.candidates.clone();
+        guesses.extend(
+            self.allowed_guess_indices()
+                .into_iter()
+                .filter(|guess| !self.candidates.contains(guess)),
+        );
+        let matrix = PatternMatrix::from_corpus_words(&self.corpus, &guesses, &self.candidates);
+        let all: Vec<usize> = (0..guesses.len()).collect();
+        let candidates = &all[..self.candidates.len()];
+        let tree = OptimalSearch::pruned(&matrix, &all, candidates, OPTIMAL_SEARCH_GUESSES)
+            .solve(candidates, max_depth)?;
+        Some(tree.map(&|guess| self.corpus.word(guesses[guess])))
+    }
+
+    fn allowed_guess_indices(&self) -> Vec<usize> {
+        (0..self.corpus.guesses().len())
+            .filter(|guess| !self.is_excluded(self.corpus.word(*guess)))
+            .filter(|guess| self.hard_mode_violation(self.corpus.word(*guess)).is_none())
+            .collect()
+    }
+}
 
 // pub struct Probability<
 //     const WORD_LENGTH: usize,
@@ -104,20 +491,6 @@ impl<const WORD_LENGTH: usize> NaiveSolver<WORD_LENGTH> {
 //     omitted: P,
 // }
 
-// impl<const WORD_LENGTH: usize> Frequencies<WORD_LENGTH> {
-//     pub fn positions(&self) -> &[usize] {
-//         &self.positions
-//     }
-// }
-
-// impl<const WORD_LENGTH: usize> Default for Frequencies<WORD_LENGTH> {
-//     fn default() -> Self {
-//         Self {
-//             positions: [0; WORD_LENGTH],
-//         }
-//     }
-// }
-
 pub struct CorpusIterator<'game, const WL: usize> {
     game: &'game NaiveSolver<WL>,
     pos: usize,
@@ -138,28 +511,14 @@ impl<'game, const WORD_LENGTH: usize> Iterator for CorpusIterator<'game, WORD_LE
     type Item = &'game str;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if self.game.corpus.len() <= self.pos {
-                return None;
-            }
-
-            let word = &self.game.corpus[self.pos];
-
-            let possible_word = &self
-                .game
-                .lines
-                .iter()
-                .all(|line| line.satisfies(word.as_str()));
-
-            if *possible_word {
-                if !self.game.is_excluded(word) {
-                    self.pos += 1;
-                    return Some(word.as_str());
-                }
-            }
+        let index = *self.game.candidates.get(self.pos)?;
+        self.pos += 1;
+        Some(self.game.corpus.word(index))
+    }
 
-            self.pos += 1;
-        }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.game.candidates.len() - self.pos;
+        (remaining, Some(remaining))
     }
 }
 
@@ -178,17 +537,183 @@ impl<const WORD_LENGTH: usize> Display for AddGuessError<WORD_LENGTH> {
             AddGuessErrorVariant::NoMoreAttempts { total_attempts: _ } => {
                 write!(f, "No more attempts")
             }
+            AddGuessErrorVariant::HardMode { violation } => {
+                write!(f, "Hard mode: {}", violation)
+            }
+            AddGuessErrorVariant::BreaksLaterGuess { line, violation } => {
+                write!(f, "Hard mode: in guess {}, {}", line + 1, violation)
+            }
         }
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum AddGuessErrorVariant {
-    WordLength { expected_length: usize },
-    NoMoreAttempts { total_attempts: usize },
+    WordLength {
+        expected_length: usize,
+    },
+    NoMoreAttempts {
+        total_attempts: usize,
+    },
+    HardMode {
+        violation: HardModeViolation,
+    },
+    /// The replacing guess reveals a hint the later guess with index `line` does not
+    /// reuse.
+    BreaksLaterGuess {
+        line: usize,
+        violation: HardModeViolation,
+    },
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// A revealed hint the guess does not reuse.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HardModeViolation {
+    MissingGood { letter: char, position: usize },
+    MissingExisting { letter: char },
+}
+
+impl Display for HardModeViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HardModeViolation::MissingGood { letter, position } => {
+                write!(f, "letter {} must be at position {}", letter, position + 1)
+            }
+            HardModeViolation::MissingExisting { letter } => {
+                write!(f, "letter {} must be used", letter)
+            }
+        }
+    }
+}
+
+/// Reason a word can not be the answer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Elimination {
+    WordLength {
+        expected_length: usize,
+        length: usize,
+    },
+    /// The word is only accepted as a guess, or not at all.
+    NotAnAnswer,
+    Excluded,
+    /// The word does not fit the feedback of the guess with index `line`.
+    Guess {
+        line: usize,
+        guess: String,
+        violation: LineViolation,
+    },
+}
+
+impl Display for Elimination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Elimination::WordLength {
+                expected_length,
+                length,
+            } => write!(
+                f,
+                "it has {} letters instead of {}",
+                length, expected_length
+            ),
+            Elimination::NotAnAnswer => write!(f, "it is not in the list of answers"),
+            Elimination::Excluded => write!(f, "it was excluded"),
+            Elimination::Guess {
+                line,
+                guess,
+                violation,
+            } => write!(f, "guess {} ({}): {}", line + 1, guess, violation),
+        }
+    }
+}
+
+/// The tile of a [`WordleLine`] a word does not agree with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineViolation {
+    WordLength {
+        expected_length: usize,
+    },
+    /// A `Good` tile has another letter.
+    MissingGood {
+        letter: char,
+        position: usize,
+    },
+    /// An `Existing` or `Inexistent` tile has the same letter.
+    LetterAtPosition {
+        letter: char,
+        position: usize,
+    },
+    TooFew {
+        letter: char,
+        required: usize,
+        found: usize,
+    },
+    TooMany {
+        letter: char,
+        allowed: usize,
+        found: usize,
+    },
+}
+
+impl Display for LineViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineViolation::WordLength { expected_length } => {
+                write!(f, "the word must have {} letters", expected_length)
+            }
+            LineViolation::MissingGood { letter, position } => {
+                write!(f, "letter {} must be at position {}", letter, position + 1)
+            }
+            LineViolation::LetterAtPosition { letter, position } => {
+                write!(
+                    f,
+                    "letter {} can not be at position {}",
+                    letter,
+                    position + 1
+                )
+            }
+            LineViolation::TooFew {
+                letter,
+                required,
+                found,
+            } => write!(
+                f,
+                "letter {} must occur at least {} times, found {}",
+                letter, required, found
+            ),
+            LineViolation::TooMany {
+                letter,
+                allowed,
+                found,
+            } => write!(
+                f,
+                "letter {} may occur at most {} times, found {}",
+                letter, allowed, found
+            ),
+        }
+    }
+}
+
+/// Guesses no word can satisfy all at once.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Contradiction {
+    /// Index of the first guess after which no word fits.
+    pub line: usize,
+    /// Single tile changes that make the guesses consistent again, starting with the ones
+    /// in the guess at `line`.
+    pub fixes: Vec<TileFix>,
+}
+
+/// Changing the tile at `position` of the guess with index `line` to `status`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TileFix {
+    pub line: usize,
+    pub position: usize,
+    pub status: WordleCharStatus,
+    /// Words that fit the guesses after the change, excluded ones included.
+    pub candidates: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct WordleLine<const WORD_LENGTH: usize> {
     pub chars: [(char, WordleCharStatus); WORD_LENGTH],
 }
@@ -198,46 +723,83 @@ impl<const WL: usize> WordleLine<WL> {
         self.chars.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
     pub fn satisfies(&self, word: &str) -> bool {
-        if word.chars().count() != self.len() {
-            return false;
-        }
-        for (word_character, (guess_character, status)) in word.chars().zip(&self.chars) {
+        self.violation(word).is_none()
+    }
+
+    /// The first tile `word` does not agree with, if any.
+    pub fn violation(&self, word: &str) -> Option<LineViolation> {
+        let word: [char; WL] = match to_chars(word) {
+            Some(word) => word,
+            None => {
+                return Some(LineViolation::WordLength {
+                    expected_length: WL,
+                })
+            }
+        };
+        let tiles = word.iter().zip(&self.chars).enumerate();
+        for (position, (word_character, (guess_character, status))) in tiles {
+            let letter = *guess_character;
             match status {
-                WordleCharStatus::Inexistent => {
-                    if word_character.eq(guess_character) {
-                        return false;
-                    }
-                }
-                WordleCharStatus::Existing => {
+                WordleCharStatus::Inexistent | WordleCharStatus::Existing => {
                     if word_character.eq(guess_character) {
-                        return false;
+                        return Some(LineViolation::LetterAtPosition { letter, position });
                     }
                 }
                 WordleCharStatus::Good => {
                     if word_character.ne(guess_character) {
-                        return false;
+                        return Some(LineViolation::MissingGood { letter, position });
                     }
                 }
             }
         }
 
-        for (guess_char, status) in &self.chars {
-            match status {
-                WordleCharStatus::Inexistent => {
-                    if word.chars().any(|c| c.eq(guess_char)) {
-                        return false;
-                    }
-                }
-                WordleCharStatus::Existing => {
-                    if word.chars().all(|c| c.ne(guess_char)) {
-                        return false;
-                    }
+        for (guess_char, _) in &self.chars {
+            let letter = *guess_char;
+            let (min, max) = self.letter_count_bounds(letter);
+            let found = word.iter().filter(|c| c.eq(&guess_char)).count();
+            if found < min {
+                return Some(LineViolation::TooFew {
+                    letter,
+                    required: min,
+                    found,
+                });
+            }
+            if let Some(allowed) = max.filter(|max| found > *max) {
+                return Some(LineViolation::TooMany {
+                    letter,
+                    allowed,
+                    found,
+                });
+            }
+        }
+        None
+    }
+
+    /// How many times `letter` may occur in the answer according to this line.
+    ///
+    /// Every `Good` or `Existing` tile proves one occurrence, and an `Inexistent` tile
+    /// of the same letter means there are no more occurrences than that.
+    pub fn letter_count_bounds(&self, letter: char) -> (usize, Option<usize>) {
+        let mut min = 0;
+        let mut capped = false;
+        for (character, status) in &self.chars {
+            if *character == letter {
+                match status {
+                    WordleCharStatus::Inexistent => capped = true,
+                    WordleCharStatus::Existing | WordleCharStatus::Good => min += 1,
                 }
-                WordleCharStatus::Good => {}
             }
         }
-        true
+        (min, capped.then_some(min))
+    }
+
+    pub fn word(&self) -> String {
+        self.chars.iter().map(|(character, _)| character).collect()
     }
 
     pub fn new(letters: [(char, WordleCharStatus); WL]) -> WordleLine<WL> {
@@ -245,7 +807,20 @@ impl<const WL: usize> WordleLine<WL> {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+/// Feedback the game gives for `guess` when the hidden word is `answer`.
+///
+/// Repeated letters are colored like in the original game: exact matches first,
+/// then `Existing` for as many remaining occurrences as the answer still has.
+///
+/// Panics if either word is not `WORD_LENGTH` characters long.
+pub fn score<const WORD_LENGTH: usize>(guess: &str, answer: &str) -> WordleLine<WORD_LENGTH> {
+    let guess: [char; WORD_LENGTH] = to_chars(guess).expect("guess has invalid length");
+    let answer: [char; WORD_LENGTH] = to_chars(answer).expect("answer has invalid length");
+    let statuses = feedback(&guess, &answer);
+    WordleLine::new(std::array::from_fn(|i| (guess[i], statuses[i])))
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum WordleCharStatus {
     Inexistent,
     Existing,
@@ -254,53 +829,131 @@ pub enum WordleCharStatus {
 
 impl<const WORD_LENGTH: usize> Solver for NaiveSolver<WORD_LENGTH> {
     type PossibleGuessesIterator<'a> = CorpusIterator<'a, WORD_LENGTH> where Self: 'a;
-    type GuessMetric = ();
+    type GuessMetric = GuessMetric;
 
-    type BestNextGuessIterator<'a> = BestNextGuessIterator<'a, WORD_LENGTH>
+    type BestNextGuessIterator<'a> = BestNextGuessIterator<'a>
     where
         Self: 'a;
 
     type Constraint = WordleLine<WORD_LENGTH>;
 
+    type ConstraintError = AddGuessError<WORD_LENGTH>;
+
     type Guess<'a> = &'a str;
 
     fn iter_possible_guesses<'a>(&'a self) -> Self::PossibleGuessesIterator<'a> {
-        todo!()
+        self.iter_corpus()
     }
 
     fn iter_best_next_guess<'a>(&'a self) -> Self::BestNextGuessIterator<'a> {
+        // the corpus comes first in `guesses`, so candidates are valid guess indices too
+        let candidates = &self.candidates;
+        let mut is_candidate = vec![false; self.corpus.guesses().len()];
+        for &candidate in candidates {
+            is_candidate[candidate] = true;
+        }
+
+        let mut guesses = self.allowed_guess_indices();
+        match self.remaining_attempts() {
+            0 => guesses.clear(),
+            // only a word that may be the answer can still win
+            1 => guesses.retain(|&guess| is_candidate[guess]),
+            _ => {}
+        }
+        let ranked = self.strategy.rank(&RankingState {
+            patterns: &|| self.pattern_matrix(),
+            words: self.corpus.guesses(),
+            guesses: &guesses,
+            candidates,
+            is_candidate: &is_candidate,
+            weights: &self.weights,
+            remaining_attempts: self.remaining_attempts(),
+        });
+
         BestNextGuessIterator {
-            inner: self.iter_corpus(),
-            taken: 0,
+            inner: ranked
+                .into_iter()
+                .map(|(guess, metric)| (self.corpus.word(guess), metric))
+                .collect::<Vec<_>>()
+                .into_iter(),
         }
     }
 
-    fn add_constraint(&mut self, constraint: Self::Constraint) {
-        todo!()
+    fn add_constraint(
+        &mut self,
+        constraint: Self::Constraint,
+    ) -> Result<(), Self::ConstraintError> {
+        self.add_guess(constraint)
+    }
+
+    fn possible_guess_count(&self) -> usize {
+        self.candidate_count()
     }
 }
 
-pub struct BestNextGuessIterator<'a, const WL: usize> {
-    inner: CorpusIterator<'a, WL>,
-    taken: usize,
+pub(crate) fn to_chars<const WORD_LENGTH: usize>(word: &str) -> Option<[char; WORD_LENGTH]> {
+    let mut chars = [char::default(); WORD_LENGTH];
+    let mut iter = word.chars();
+    for slot in chars.iter_mut() {
+        *slot = iter.next()?;
+    }
+    match iter.next() {
+        Some(_) => None,
+        None => Some(chars),
+    }
 }
 
-impl<'a, const WL: usize> Iterator for BestNextGuessIterator<'a, WL> {
-    type Item = (&'a str, ());
+pub(crate) fn feedback<T: Copy + PartialEq, const WORD_LENGTH: usize>(
+    guess: &[T; WORD_LENGTH],
+    answer: &[T; WORD_LENGTH],
+) -> [WordleCharStatus; WORD_LENGTH] {
+    let mut statuses = [WordleCharStatus::Inexistent; WORD_LENGTH];
+    feedback_into(guess, answer, &mut statuses);
+    statuses
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.taken < 10 {
-            self.taken += 1;
-            Some((self.inner.next()?, ()))
+/// Same as [`feedback`] for words whose length is only known at runtime. `statuses` must
+/// be as long as the words and start out `Inexistent`.
+pub(crate) fn feedback_into<T: Copy + PartialEq>(
+    guess: &[T],
+    answer: &[T],
+    statuses: &mut [WordleCharStatus],
+) {
+    let mut unmatched = Vec::with_capacity(guess.len());
+    for i in 0..guess.len() {
+        if guess[i] == answer[i] {
+            statuses[i] = WordleCharStatus::Good;
         } else {
-            None
+            unmatched.push(answer[i]);
         }
     }
+    for i in 0..guess.len() {
+        if statuses[i] == WordleCharStatus::Good {
+            continue;
+        }
+        if let Some(pos) = unmatched.iter().position(|c| *c == guess[i]) {
+            unmatched.swap_remove(pos);
+            statuses[i] = WordleCharStatus::Existing;
+        }
+    }
+}
+
+pub struct BestNextGuessIterator<'a> {
+    inner: std::vec::IntoIter<(&'a str, GuessMetric)>,
+}
+
+impl<'a> Iterator for BestNextGuessIterator<'a> {
+    type Item = (&'a str, GuessMetric);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
 }
 
 #[cfg(test)]
 mod tets {
     use super::*;
+    use crate::solvers::wordle::words;
 
     #[test]
     fn filter_corpus_after_successful_guess() {
@@ -327,20 +980,426 @@ mod tets {
         assert_eq!(vec!["cba"], filtered_corpus);
     }
 
-    // #[test]
-    // fn vocabulary() {
-    //     let corpus = vec![
-    //         "aaa".to_string(),
-    //         "bbb".to_string(),
-    //         "ccc".to_string(),
-    //         "abc".to_string(),
-    //         "cba".to_string(),
-    //     ];
-    //     let game: NaiveSolver<3, 6> = NaiveSolver::new(corpus);
-    //     let (count, map) = game.get_frequencies();
-    //     assert_eq!(count, 5);
-    //     assert_eq!(map[&'a'].positions(), [2, 1, 2]);
-    //     assert_eq!(map[&'b'].positions(), [1, 3, 1]);
-    //     assert_eq!(map[&'c'].positions(), [2, 1, 2]);
-    // }
+    fn play<S: Solver>(solver: &mut S, constraints: Vec<S::Constraint>) -> Vec<S::Guess<'_>> {
+        for constraint in constraints {
+            assert!(solver.add_constraint(constraint).is_ok());
+        }
+        solver.iter_possible_guesses().collect()
+    }
+
+    #[test]
+    fn solver_trait_drives_the_game() {
+        let corpus = words(&["abc", "abd", "abe", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+
+        let possible = play(&mut game, vec![score("abx", "abd"), score("abc", "abd")]);
+        assert_eq!(vec!["abd", "abe"], possible);
+        assert_eq!(2, game.possible_guess_count());
+        assert_eq!(2, game.iter_possible_guesses().count());
+        assert_eq!(2, game.performed_guesses());
+    }
+
+    #[test]
+    fn explain_eliminated_words() {
+        let corpus = words(&["метро", "театр", "отток", "рокот"]);
+        let mut game: NaiveSolver<5> = NaiveSolver::new(corpus);
+        assert_eq!(Ok(()), game.add_guess(score("театр", "отток")));
+        assert!(game.exclude("рокот".to_string()).is_ok());
+
+        assert_eq!(None, game.explain("отток"));
+        assert_eq!(Some(Elimination::Excluded), game.explain("рокот"));
+        assert_eq!(Some(Elimination::NotAnAnswer), game.explain("шпрот"));
+        assert_eq!(
+            Some(Elimination::WordLength {
+                expected_length: 5,
+                length: 3
+            }),
+            game.explain("ток")
+        );
+        assert_eq!(
+            Some(Elimination::Guess {
+                line: 0,
+                guess: "театр".to_string(),
+                violation: LineViolation::LetterAtPosition {
+                    letter: 'е',
+                    position: 1
+                },
+            }),
+            game.explain("метро")
+        );
+    }
+
+    #[test]
+    fn contradiction_points_to_fixable_tile() {
+        use WordleCharStatus::*;
+
+        let corpus = words(&["abc", "abd", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        assert_eq!(Ok(()), game.add_guess(score("abx", "abd")));
+        assert_eq!(None, game.contradiction());
+        // 'd' typed as missing
+        let typo = WordleLine::new([('x', Inexistent), ('y', Inexistent), ('d', Inexistent)]);
+        assert_eq!(Ok(()), game.add_guess(typo));
+        assert_eq!(Ok(()), game.add_guess(score("abc", "abd")));
+
+        let contradiction = game.contradiction().unwrap();
+        assert_eq!(2, contradiction.line);
+        assert!(contradiction.fixes.contains(&TileFix {
+            line: 1,
+            position: 2,
+            status: Good,
+            candidates: 1,
+        }));
+        assert!(contradiction.fixes.iter().all(|fix| fix.candidates > 0));
+    }
+
+    #[test]
+    fn pattern_cache_is_only_used_when_needed() {
+        use crate::solvers::strategy::FrequencyHeuristic;
+
+        let corpus = words(&["abc", "abd", "abe", "xyz"]);
+        let path = std::env::temp_dir().join(format!("wordle_{}.patterns", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        game.use_pattern_cache(path.clone());
+        game.set_strategy(Arc::new(FrequencyHeuristic { top_k: 3 }));
+        assert_eq!(3, game.iter_best_next_guess().count());
+        let expected_guesses = game.optimal_decision_tree(3).unwrap().expected_guesses;
+        assert!(!path.exists());
+
+        game.set_strategy(Arc::new(Ranking::Entropy));
+        assert_eq!(4, game.iter_best_next_guess().count());
+        assert!(path.exists());
+        assert!(game.take_pattern_cache_error().is_none());
+        let all: Vec<usize> = (0..4).collect();
+        let tree = OptimalSearch::new(game.pattern_matrix(), all.clone()).solve(&all, 3);
+        assert_eq!(expected_guesses, tree.unwrap().expected_guesses);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn excluding_the_last_candidate_is_no_contradiction() {
+        let corpus = words(&["abc", "abd", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        assert_eq!(Ok(()), game.add_guess(score("abx", "abd")));
+        assert_eq!(Ok(()), game.add_guess(score("abc", "abd")));
+        assert!(game.exclude("abd".to_string()).is_ok());
+
+        assert_eq!(0, game.candidate_count());
+        assert_eq!(None, game.contradiction());
+
+        // 'd' typed as missing: blamed on that guess, not on the one that left 'abd' only
+        let typo = WordleLine::new([
+            ('x', WordleCharStatus::Inexistent),
+            ('b', WordleCharStatus::Good),
+            ('d', WordleCharStatus::Inexistent),
+        ]);
+        assert_eq!(Ok(()), game.add_guess(typo));
+        let contradiction = game.contradiction().unwrap();
+        assert_eq!(2, contradiction.line);
+        assert_eq!(
+            Some(&TileFix {
+                line: 2,
+                position: 2,
+                status: WordleCharStatus::Good,
+                candidates: 1,
+            }),
+            contradiction.fixes.first()
+        );
+    }
+
+    #[test]
+    fn attempts_are_limited() {
+        let corpus = words(&["abc", "abd", "abe", "xyz", "xbd"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        game.set_max_attempts(2);
+        assert_eq!(Ok(()), game.add_guess(score("xyz", "abd")));
+        assert_eq!(1, game.remaining_attempts());
+
+        // a probe word can no longer win
+        assert!(game
+            .iter_best_next_guess()
+            .all(|(_, metric)| metric.is_candidate));
+
+        assert_eq!(Ok(()), game.add_guess(score("abc", "abd")));
+        assert_eq!(
+            AddGuessErrorVariant::NoMoreAttempts { total_attempts: 2 },
+            game.add_guess(score("abd", "abd")).unwrap_err().error
+        );
+        assert_eq!(0, game.iter_best_next_guess().count());
+    }
+
+    #[test]
+    fn undo_redo_and_replace_guesses() {
+        let corpus = words(&["abc", "abd", "abe", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        assert_eq!(Ok(()), game.add_guess(score("abx", "abd")));
+        assert_eq!(Ok(()), game.add_guess(score("abc", "abd")));
+        assert_eq!(2, game.candidate_count());
+
+        assert_eq!(Some(score("abc", "abd")), game.pop_guess());
+        assert_eq!(1, game.performed_guesses());
+        assert_eq!(3, game.candidate_count());
+        assert_eq!(Some(Ok(&score("abc", "abd"))), game.redo());
+        assert_eq!(None, game.redo());
+        assert_eq!(2, game.history().len());
+
+        let replaced = game.replace_guess(1, score("abe", "abe"));
+        assert_eq!(Some(Ok(score("abc", "abd"))), replaced);
+        assert_eq!(vec!["abe"], game.iter_corpus().collect::<Vec<_>>());
+        assert_eq!(None, game.replace_guess(2, score("abc", "abc")));
+    }
+
+    #[test]
+    fn redo_and_replace_check_guesses_like_new_ones() {
+        let corpus = words(&["abc", "abd", "abe", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        game.set_hard_mode(true);
+        assert_eq!(Ok(()), game.add_guess(score("abx", "abd")));
+        assert_eq!(Ok(()), game.add_guess(score("abc", "abd")));
+
+        let replaced = game.replace_guess(1, score("xyz", "abd"));
+        assert_eq!(
+            AddGuessErrorVariant::HardMode {
+                violation: HardModeViolation::MissingGood {
+                    letter: 'a',
+                    position: 0
+                }
+            },
+            replaced.unwrap().unwrap_err().error
+        );
+        assert_eq!(score("abc", "abd"), game.history()[1]);
+        // nothing was played before the first guess
+        assert!(game.replace_guess(0, score("xyz", "abd")).unwrap().is_ok());
+        // a 'd' revealed in the first guess is missing from the second one
+        let replaced = game.replace_guess(0, score("xyd", "abd"));
+        assert_eq!(
+            AddGuessErrorVariant::BreaksLaterGuess {
+                line: 1,
+                violation: HardModeViolation::MissingGood {
+                    letter: 'd',
+                    position: 2
+                }
+            },
+            replaced.unwrap().unwrap_err().error
+        );
+        assert_eq!(score("xyz", "abd"), game.history()[0]);
+
+        game.pop_guess();
+        game.set_max_attempts(1);
+        assert_eq!(
+            AddGuessErrorVariant::NoMoreAttempts { total_attempts: 1 },
+            game.redo().unwrap().unwrap_err().error
+        );
+        assert_eq!(1, game.history().len());
+        game.set_max_attempts(2);
+        assert_eq!(Some(Ok(&score("abc", "abd"))), game.redo());
+    }
+
+    #[test]
+    fn candidates_narrow_with_guesses_and_exclusions() {
+        let corpus = words(&["abc", "abd", "abe", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        assert_eq!(4, game.candidate_count());
+
+        assert_eq!(Ok(()), game.add_guess(score("abx", "abd")));
+        assert_eq!(3, game.candidate_count());
+        assert!(game.exclude("abe".to_string()).is_ok());
+        assert_eq!(2, game.candidate_count());
+
+        let mut weights = HashMap::new();
+        weights.insert("abd".to_string(), 2.0);
+        game.set_weights(&weights, 1.0);
+        assert_eq!(vec!["abd", "abc"], game.iter_corpus().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn exclude_counts_letters_not_bytes() {
+        let corpus = words(&["метро", "театр"]);
+        let mut game: NaiveSolver<5> = NaiveSolver::new(corpus);
+
+        assert!(game.exclude("метро".to_string()).is_ok());
+        assert_eq!(vec!["театр"], game.iter_corpus().collect::<Vec<_>>());
+        assert!(game.exclude("мет".to_string()).is_err());
+    }
+
+    #[test]
+    fn hard_mode_requires_revealed_hints() {
+        use WordleCharStatus::*;
+
+        let corpus = words(&["abc", "abd", "bcd", "dbe", "xyz"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        game.set_hard_mode(true);
+        let first = WordleLine::new([('a', Inexistent), ('b', Good), ('d', Existing)]);
+        assert_eq!(Ok(()), game.add_guess(first));
+
+        let ignores_good = WordleLine::new([('d', Inexistent), ('c', Inexistent), ('b', Existing)]);
+        assert_eq!(
+            AddGuessErrorVariant::HardMode {
+                violation: HardModeViolation::MissingGood {
+                    letter: 'b',
+                    position: 1
+                }
+            },
+            game.add_guess(ignores_good).unwrap_err().error
+        );
+        let ignores_existing = WordleLine::new([('x', Inexistent), ('b', Good), ('c', Inexistent)]);
+        assert_eq!(
+            AddGuessErrorVariant::HardMode {
+                violation: HardModeViolation::MissingExisting { letter: 'd' }
+            },
+            game.add_guess(ignores_existing).unwrap_err().error
+        );
+
+        let guesses: Vec<&str> = game.iter_best_next_guess().map(|(word, _)| word).collect();
+        assert_eq!(vec!["dbe", "abd"], guesses);
+    }
+
+    #[test]
+    fn best_next_guess_maximizes_entropy() {
+        let corpus = words(&["abc", "abd", "abe", "cde"]);
+        let game: NaiveSolver<3> = NaiveSolver::new(corpus);
+
+        let ranked: Vec<(&str, GuessMetric)> = game.iter_best_next_guess().collect();
+
+        // "cde" tells every candidate apart, "abc" can't distinguish "abd" from "abe"
+        assert_eq!("cde", ranked[0].0);
+        assert!((ranked[0].1.entropy - 2.0).abs() < 1e-9);
+        assert!(ranked[1].1.entropy < ranked[0].1.entropy);
+        assert_eq!(4, ranked.len());
+    }
+
+    #[test]
+    fn probe_words_are_ranked_but_never_candidates() {
+        let corpus = words(&["aab", "aac", "aad"]);
+        let allowed = words(&["bcd", "aab", "xyzw"]);
+        let game: NaiveSolver<3> = NaiveSolver::with_allowed_guesses(corpus, allowed);
+
+        let ranked: Vec<(&str, GuessMetric)> = game.iter_best_next_guess().collect();
+
+        assert_eq!(("bcd", false), (ranked[0].0, ranked[0].1.is_candidate));
+        assert_eq!(4, ranked.len());
+        assert_eq!(
+            vec!["aab", "aac", "aad"],
+            game.iter_corpus().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn weights_order_candidates_and_information() {
+        let corpus = words(&["aab", "aac", "aad", "bcd"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        let uniform = game.iter_best_next_guess().next().unwrap().1.entropy;
+
+        let weights = HashMap::from([("aad".to_string(), 100.0), ("bcd".to_string(), 10.0)]);
+        game.set_weights(&weights, 1.0);
+
+        assert_eq!(
+            vec!["aad", "bcd", "aab", "aac"],
+            game.iter_corpus().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(1.0), game.weight("aab"));
+        let weighted = game.iter_best_next_guess().next().unwrap().1.entropy;
+        assert!(weighted < uniform);
+    }
+
+    #[test]
+    fn minimax_prefers_smallest_worst_case() {
+        let corpus = words(&["aaa", "bbd", "bfb", "cce", "dda", "eca", "fce"]);
+        let mut game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        assert_eq!("fce", game.iter_best_next_guess().next().unwrap().0);
+
+        game.set_strategy(Arc::new(Ranking::Minimax));
+        let ranked: Vec<(&str, GuessMetric)> = game.iter_best_next_guess().collect();
+
+        assert_eq!("eca", ranked[0].0);
+        assert_eq!(2, ranked[0].1.largest_bucket);
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].1.largest_bucket <= pair[1].1.largest_bucket));
+    }
+
+    #[test]
+    fn repeated_letter_with_grey_tile_keeps_single_occurrence() {
+        use WordleCharStatus::*;
+
+        let line = WordleLine::new([
+            ('о', Existing),
+            ('т', Inexistent),
+            ('т', Good),
+            ('о', Inexistent),
+            ('к', Inexistent),
+        ]);
+
+        assert!(line.satisfies("метро"));
+        assert!(!line.satisfies("этто"));
+        assert!(!line.satisfies("оттар"));
+        assert!(!line.satisfies("астра"));
+        assert_eq!((1, Some(1)), line.letter_count_bounds('т'));
+        assert_eq!((0, None), line.letter_count_bounds('я'));
+    }
+
+    #[test]
+    fn answer_always_satisfies_its_own_score() {
+        let words = ["метро", "театр", "отток", "аттик", "ттттт", "татар", "рокот"];
+        for guess in words {
+            for answer in words {
+                let line: WordleLine<5> = score(guess, answer);
+                assert!(line.satisfies(answer), "{} / {}", guess, answer);
+            }
+        }
+    }
+
+    #[test]
+    fn score_colors_repeated_letters_by_remaining_count() {
+        use WordleCharStatus::*;
+
+        let line: WordleLine<5> = score("метро", "театр");
+        assert_eq!(
+            [
+                ('м', Inexistent),
+                ('е', Good),
+                ('т', Existing),
+                ('р', Existing),
+                ('о', Inexistent),
+            ],
+            line.chars
+        );
+
+        let line: WordleLine<5> = score("ттттт", "театр");
+        assert_eq!(
+            [
+                ('т', Good),
+                ('т', Inexistent),
+                ('т', Inexistent),
+                ('т', Good),
+                ('т', Inexistent),
+            ],
+            line.chars
+        );
+
+        let line: WordleLine<5> = score("аттик", "театр");
+        assert_eq!(
+            [
+                ('а', Existing),
+                ('т', Existing),
+                ('т', Existing),
+                ('и', Inexistent),
+                ('к', Inexistent),
+            ],
+            line.chars
+        );
+    }
+
+    #[test]
+    fn vocabulary() {
+        let corpus = words(&["aaa", "bbb", "ccc", "abc", "cba"]);
+        let game: NaiveSolver<3> = NaiveSolver::new(corpus);
+        let frequencies = game.letter_frequencies();
+        assert_eq!(5.0, frequencies.total());
+        assert_eq!([2.0, 1.0, 2.0], [0, 1, 2].map(|i| frequencies.at('a', i)));
+        assert_eq!([1.0, 3.0, 1.0], [0, 1, 2].map(|i| frequencies.at('b', i)));
+        assert_eq!([2.0, 1.0, 2.0], [0, 1, 2].map(|i| frequencies.at('c', i)));
+        assert_eq!(3.0, frequencies.anywhere('a'));
+    }
 }
@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+/// How often letters occur in a set of words, every word counted with its weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frequencies {
+    total: f64,
+    /// Weight of the words with the letter at every position.
+    positions: Vec<HashMap<char, f64>>,
+    /// Weight of the words with the letter anywhere.
+    words: HashMap<char, f64>,
+}
+
+impl Frequencies {
+    pub fn new<'a>(word_length: usize, words: impl IntoIterator<Item = (&'a str, f64)>) -> Self {
+        let mut frequencies = Self {
+            total: 0.0,
+            positions: vec![HashMap::new(); word_length],
+            words: HashMap::new(),
+        };
+        for (word, weight) in words {
+            for (position, letter) in word.chars().take(word_length).enumerate() {
+                *frequencies.positions[position].entry(letter).or_default() += weight;
+            }
+            for letter in word.chars().collect::<HashSet<_>>() {
+                *frequencies.words.entry(letter).or_default() += weight;
+            }
+            frequencies.total += weight;
+        }
+        frequencies
+    }
+
+    /// Weight of all words.
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    pub fn at(&self, letter: char, position: usize) -> f64 {
+        self.positions
+            .get(position)
+            .and_then(|letters| letters.get(&letter))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn anywhere(&self, letter: char) -> f64 {
+        self.words.get(&letter).copied().unwrap_or(0.0)
+    }
+
+    /// Information, in bits, `guess` gives about a random word of the set when every
+    /// tile is taken separately: whether the word has the letter at that position and
+    /// whether it has the letter at all.
+    pub fn score(&self, guess: &str) -> f64 {
+        if self.total <= 0.0 {
+            return 0.0;
+        }
+        let positional: f64 = guess
+            .chars()
+            .enumerate()
+            .map(|(position, letter)| binary_entropy(self.at(letter, position) / self.total))
+            .sum();
+        let anywhere: f64 = guess
+            .chars()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|letter| binary_entropy(self.anywhere(letter) / self.total))
+            .sum();
+        positional + anywhere
+    }
+}
+
+fn binary_entropy(p: f64) -> f64 {
+    if p <= 0.0 || p >= 1.0 {
+        0.0
+    } else {
+        -p * p.log2() - (1.0 - p) * (1.0 - p).log2()
+    }
+}
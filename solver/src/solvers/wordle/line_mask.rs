@@ -0,0 +1,88 @@
+use super::corpus::{Corpus, EncodedWord, LetterMask};
+use super::fixed_size_wordle_solver::{WordleCharStatus, WordleLine};
+
+/// [`WordleLine`] compiled against a corpus alphabet, so checking an encoded word takes a
+/// few bitwise operations per letter position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineMask<const WORD_LENGTH: usize> {
+    /// Letters allowed at every position.
+    allowed: [LetterMask; WORD_LENGTH],
+    /// `required[k]` holds the letters that must occur more than `k` times.
+    required: [LetterMask; WORD_LENGTH],
+    /// `forbidden[k]` holds the letters that must not occur more than `k` times.
+    forbidden: [LetterMask; WORD_LENGTH],
+}
+
+impl<const WORD_LENGTH: usize> LineMask<WORD_LENGTH> {
+    pub fn compile(line: &WordleLine<WORD_LENGTH>, corpus: &Corpus<WORD_LENGTH>) -> Self {
+        let mut mask = Self {
+            allowed: [LetterMask::MAX; WORD_LENGTH],
+            required: [0; WORD_LENGTH],
+            forbidden: [0; WORD_LENGTH],
+        };
+        for (position, (letter, status)) in line.chars.iter().enumerate() {
+            let bit = corpus.letter_index(*letter).map_or(0, |index| 1 << index);
+            match status {
+                WordleCharStatus::Good => mask.allowed[position] = bit,
+                WordleCharStatus::Inexistent | WordleCharStatus::Existing => {
+                    mask.allowed[position] &= !bit
+                }
+            }
+        }
+        for (letter, _) in &line.chars {
+            let (min, max) = line.letter_count_bounds(*letter);
+            match corpus.letter_index(*letter) {
+                Some(index) => {
+                    if min > 0 {
+                        mask.required[min - 1] |= 1 << index;
+                    }
+                    if let Some(max) = max.filter(|max| *max < WORD_LENGTH) {
+                        mask.forbidden[max] |= 1 << index;
+                    }
+                }
+                // no corpus word has the letter
+                None if min > 0 => mask.allowed = [0; WORD_LENGTH],
+                None => {}
+            }
+        }
+        mask
+    }
+
+    /// Same as [`WordleLine::satisfies`] for the word `word` encodes.
+    pub fn matches(&self, word: &EncodedWord<WORD_LENGTH>) -> bool {
+        (0..WORD_LENGTH).all(|i| {
+            self.allowed[i] & (1 << word.letters[i]) != 0
+                && word.counts[i] & self.required[i] == self.required[i]
+                && word.counts[i] & self.forbidden[i] == 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::fixed_size_wordle_solver::score;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn mask_agrees_with_line() {
+        let words = words(&["метро", "театр", "отток", "аттик", "татар", "рокот"]);
+        let corpus: Corpus<5> = Corpus::new(words.clone());
+        let mut lines: Vec<WordleLine<5>> = vec![];
+        for guess in words.iter().map(String::as_str).chain(["шпрот", "ттттт"]) {
+            for answer in &words {
+                lines.push(score(guess, answer));
+            }
+        }
+        let mut unknown_letter = score::<5>("метро", "театр");
+        unknown_letter.chars[0] = ('ш', WordleCharStatus::Existing);
+        lines.push(unknown_letter);
+
+        for line in &lines {
+            let mask = LineMask::compile(line, &corpus);
+            for (index, word) in words.iter().enumerate() {
+                assert_eq!(line.satisfies(word), mask.matches(corpus.encoded(index)));
+            }
+        }
+    }
+}
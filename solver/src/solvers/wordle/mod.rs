@@ -1,2 +1,14 @@
+pub mod corpus;
 pub mod dynamic_wordle_solver;
-pub mod fixed_size_wordle_solver;
\ No newline at end of file
+pub mod fixed_size_wordle_solver;
+pub mod frequencies;
+pub mod line_mask;
+pub mod optimal;
+pub mod pattern_matrix;
+pub mod ranking;
+
+/// Owned copies of `words`, for test fixtures.
+#[cfg(test)]
+pub(crate) fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
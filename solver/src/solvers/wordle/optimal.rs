@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use super::pattern_matrix::{solved_pattern, Pattern, PatternMatrix};
+
+/// Strategy that finds every candidate, starting with `guess` and following the branch
+/// for the feedback received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionTree<G = usize> {
+    pub guess: G,
+    /// Guesses needed on average, this one included.
+    pub expected_guesses: f64,
+    /// Subtrees for every feedback except the solved one.
+    pub branches: Vec<(Pattern, DecisionTree<G>)>,
+}
+
+impl<G> DecisionTree<G> {
+    pub fn map<H>(self, f: &impl Fn(G) -> H) -> DecisionTree<H> {
+        DecisionTree {
+            guess: f(self.guess),
+            expected_guesses: self.expected_guesses,
+            branches: self
+                .branches
+                .into_iter()
+                .map(|(pattern, tree)| (pattern, tree.map(f)))
+                .collect(),
+        }
+    }
+
+    pub fn branch(&self, pattern: Pattern) -> Option<&DecisionTree<G>> {
+        self.branches
+            .iter()
+            .find(|(branch, _)| *branch == pattern)
+            .map(|(_, tree)| tree)
+    }
+}
+
+type Buckets = Vec<(Pattern, Vec<usize>)>;
+type Memo = HashMap<(Vec<usize>, usize), Option<(f64, usize)>>;
+
+/// Pattern lookups a search may do before it gives up, a second or two of work.
+pub const DEFAULT_BUDGET: usize = 20_000_000;
+
+/// Exhaustive search for the guess minimizing the expected number of guesses.
+///
+/// Only practical for small candidate sets: every guess is tried for every group of
+/// candidates it can leave, memoized on the group and the remaining depth. The search
+/// gives up once it has looked up more patterns than its budget allows.
+pub struct OptimalSearch<'m> {
+    matrix: &'m PatternMatrix,
+    guesses: Vec<usize>,
+    solved: Pattern,
+    /// Expected guesses and the best first guess by candidate group and remaining depth.
+    memo: Memo,
+    /// Pattern lookups left, `None` once the search gave up.
+    budget: Option<usize>,
+}
+
+impl<'m> OptimalSearch<'m> {
+    pub fn new(matrix: &'m PatternMatrix, guesses: Vec<usize>) -> Self {
+        Self {
+            matrix,
+            guesses,
+            solved: solved_pattern(matrix.word_length()),
+            memo: HashMap::new(),
+            budget: Some(DEFAULT_BUDGET),
+        }
+    }
+
+    /// Search over the `candidates` and the `top_k` other `guesses` with the most
+    /// information about them. The other guesses rarely make a better tree, and leaving
+    /// them out keeps the search affordable for large word lists.
+    pub fn pruned(
+        matrix: &'m PatternMatrix,
+        guesses: &[usize],
+        candidates: &[usize],
+        top_k: usize,
+    ) -> Self {
+        let total = candidates.len() as f64;
+        let mut others: Vec<(f64, usize)> = guesses
+            .iter()
+            .filter(|guess| !candidates.contains(guess))
+            .map(|&guess| {
+                let entropy: f64 = matrix
+                    .partition(guess, candidates)
+                    .iter()
+                    .map(|(_, bucket)| {
+                        let p = bucket.len() as f64 / total;
+                        -p * p.log2()
+                    })
+                    .sum();
+                (entropy, guess)
+            })
+            .collect();
+        others.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        let mut kept: Vec<usize> = guesses
+            .iter()
+            .copied()
+            .filter(|guess| candidates.contains(guess))
+            .collect();
+        kept.extend(others.into_iter().take(top_k).map(|(_, guess)| guess));
+        Self::new(matrix, kept)
+    }
+
+    /// Limits the pattern lookups of the search, [`DEFAULT_BUDGET`] by default.
+    pub fn with_budget(mut self, lookups: usize) -> Self {
+        self.budget = Some(lookups);
+        self
+    }
+
+    /// Best tree finding every candidate in at most `max_depth` guesses, if there is one
+    /// and the budget suffices to find it.
+    pub fn solve(&mut self, candidates: &[usize], max_depth: usize) -> Option<DecisionTree> {
+        if candidates.is_empty() {
+            return None;
+        }
+        let mut candidates = candidates.to_vec();
+        candidates.sort_unstable();
+        self.expected_guesses(&candidates, max_depth)?;
+        self.budget?;
+        Some(self.build(candidates, max_depth))
+    }
+
+    fn expected_guesses(&mut self, candidates: &[usize], depth: usize) -> Option<(f64, usize)> {
+        if depth == 0 {
+            return None;
+        }
+        let key = (candidates.to_vec(), depth);
+        if let Some(known) = self.memo.get(&key) {
+            return *known;
+        }
+        let lookups = self.guesses.len() * candidates.len();
+        self.budget = self.budget?.checked_sub(lookups);
+        self.budget?;
+
+        let total = candidates.len() as f64;
+        let mut options: Vec<(f64, usize, Buckets)> = self
+            .guesses
+            .iter()
+            .filter_map(|&guess| {
+                let buckets = self.matrix.partition(guess, candidates);
+                if buckets.len() == 1 && buckets[0].0 != self.solved {
+                    return None;
+                }
+                let lower_bound = 1.0
+                    + buckets
+                        .iter()
+                        .filter(|(pattern, _)| *pattern != self.solved)
+                        .map(|(_, bucket)| {
+                            let size = bucket.len() as f64;
+                            size / total * (2.0 - 1.0 / size)
+                        })
+                        .sum::<f64>();
+                Some((lower_bound, guess, buckets))
+            })
+            .collect();
+        options.sort_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+
+        let mut best: Option<(f64, usize)> = None;
+        'options: for (lower_bound, guess, buckets) in options {
+            if best.is_some_and(|(cost, _)| lower_bound >= cost) {
+                break;
+            }
+            let mut cost = 1.0;
+            for (pattern, bucket) in buckets {
+                if pattern == self.solved {
+                    continue;
+                }
+                match self.expected_guesses(&bucket, depth - 1) {
+                    Some((bucket_cost, _)) => cost += bucket.len() as f64 / total * bucket_cost,
+                    None => continue 'options,
+                }
+            }
+            let improves = match best {
+                Some((best_cost, _)) => cost < best_cost,
+                None => true,
+            };
+            if improves {
+                best = Some((cost, guess));
+            }
+        }
+
+        self.memo.insert(key, best);
+        best
+    }
+
+    fn build(&self, candidates: Vec<usize>, depth: usize) -> DecisionTree {
+        let key = (candidates, depth);
+        let (expected_guesses, guess) = self.memo[&key].expect("only solvable groups are built");
+        let branches = self
+            .matrix
+            .partition(guess, &key.0)
+            .into_iter()
+            .filter(|(pattern, _)| *pattern != self.solved)
+            .map(|(pattern, bucket)| (pattern, self.build(bucket, depth - 1)))
+            .collect();
+
+        DecisionTree {
+            guess,
+            expected_guesses,
+            branches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::words;
+
+    fn guesses_needed(tree: &DecisionTree, matrix: &PatternMatrix, answer: usize) -> usize {
+        let pattern = matrix.get(tree.guess, answer);
+        match tree.branch(pattern) {
+            Some(subtree) => 1 + guesses_needed(subtree, matrix, answer),
+            None => 1,
+        }
+    }
+
+    #[test]
+    fn tree_finds_every_candidate_within_depth() {
+        let words = words(&["aab", "aac", "aad", "aae", "bcd", "xyz", "eda"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let candidates: Vec<usize> = (0..words.len()).collect();
+        let mut search = OptimalSearch::new(&matrix, candidates.clone());
+
+        let tree = search.solve(&candidates, 3).unwrap();
+
+        let needed: Vec<usize> = candidates
+            .iter()
+            .map(|&answer| guesses_needed(&tree, &matrix, answer))
+            .collect();
+        assert!(needed.iter().all(|&guesses| guesses <= 3));
+        let average = needed.iter().sum::<usize>() as f64 / needed.len() as f64;
+        assert!((tree.expected_guesses - average).abs() < 1e-9);
+        assert!(search.solve(&candidates, 1).is_none());
+    }
+
+    #[test]
+    fn search_gives_up_over_budget() {
+        let words = words(&["aab", "aac", "aad", "aae", "bcd", "xyz", "eda"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let candidates: Vec<usize> = (0..4).collect();
+
+        let mut search = OptimalSearch::new(&matrix, candidates.clone()).with_budget(10);
+        assert!(search.solve(&candidates, 3).is_none());
+        // a memoized group does not cost anything, but the search stays given up
+        assert!(search.solve(&candidates, 3).is_none());
+
+        let mut search = OptimalSearch::pruned(&matrix, &[6, 5, 4, 3, 2, 1, 0], &candidates, 1);
+        assert_eq!(vec![3, 2, 1, 0, 4], search.guesses);
+        assert_eq!(4, search.solve(&candidates, 2).unwrap().guess);
+    }
+}
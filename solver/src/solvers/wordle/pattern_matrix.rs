@@ -0,0 +1,443 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use super::corpus::Corpus;
+use super::fixed_size_wordle_solver::{feedback, to_chars, WordleCharStatus, WordleLine};
+
+/// Feedback of a whole line encoded as a base-3 number, the first tile being the most
+/// significant digit (`Inexistent` = 0, `Existing` = 1, `Good` = 2).
+pub type Pattern = u16;
+
+pub const MAX_WORD_LENGTH: usize = 10;
+
+pub fn encode<const WORD_LENGTH: usize>(statuses: &[WordleCharStatus; WORD_LENGTH]) -> Pattern {
+    statuses.iter().fold(0, |pattern, status| {
+        pattern * 3
+            + match status {
+                WordleCharStatus::Inexistent => 0,
+                WordleCharStatus::Existing => 1,
+                WordleCharStatus::Good => 2,
+            }
+    })
+}
+
+pub fn decode<const WORD_LENGTH: usize>(mut pattern: Pattern) -> [WordleCharStatus; WORD_LENGTH] {
+    let mut statuses = [WordleCharStatus::Inexistent; WORD_LENGTH];
+    for status in statuses.iter_mut().rev() {
+        *status = match pattern % 3 {
+            0 => WordleCharStatus::Inexistent,
+            1 => WordleCharStatus::Existing,
+            _ => WordleCharStatus::Good,
+        };
+        pattern /= 3;
+    }
+    statuses
+}
+
+pub fn pattern_of<const WORD_LENGTH: usize>(line: &WordleLine<WORD_LENGTH>) -> Pattern {
+    encode(&line.chars.map(|(_, status)| status))
+}
+
+/// Pattern of a line where every tile is `Good`.
+pub fn solved_pattern(word_length: usize) -> Pattern {
+    pattern_count(word_length) as Pattern - 1
+}
+
+pub fn pattern_count(word_length: usize) -> usize {
+    3usize.pow(word_length as u32)
+}
+
+const CACHE_MAGIC: &[u8; 4] = b"WSPM";
+const CACHE_VERSION: u32 = 1;
+
+/// Identifies the word lists a matrix was built from, stable between runs and platforms.
+pub fn corpus_key(word_length: usize, guesses: &[String], answers: &[String]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    feed(&(word_length as u64).to_le_bytes());
+    for list in [guesses, answers] {
+        for word in list {
+            feed(word.as_bytes());
+            feed(&[0xff]);
+        }
+        feed(&[0xfe]);
+    }
+    hash
+}
+
+/// Feedback for every (guess, answer) pair, computed once up front.
+pub struct PatternMatrix {
+    word_length: usize,
+    key: u64,
+    guesses: usize,
+    answers: usize,
+    patterns: Vec<Pattern>,
+}
+
+impl PatternMatrix {
+    pub fn new<const WORD_LENGTH: usize>(guesses: &[String], answers: &[String]) -> Self {
+        let spell = |word: &String| -> [char; WORD_LENGTH] {
+            to_chars(word).expect("word has invalid length")
+        };
+        let guess_chars: Vec<_> = guesses.iter().map(spell).collect();
+        let answer_chars: Vec<_> = answers.iter().map(spell).collect();
+        Self::build(
+            corpus_key(WORD_LENGTH, guesses, answers),
+            &guess_chars,
+            &answer_chars,
+        )
+    }
+
+    /// Same as [`new`](Self::new) for the corpus guesses and answers, scoring the encoded
+    /// words instead of spelling them out again.
+    pub fn from_corpus<const WORD_LENGTH: usize>(corpus: &Corpus<WORD_LENGTH>) -> Self {
+        let letters: Vec<[u8; WORD_LENGTH]> = (0..corpus.guesses().len())
+            .map(|index| corpus.encoded(index).letters)
+            .collect();
+        Self::build(
+            corpus_key(WORD_LENGTH, corpus.guesses(), corpus.answers()),
+            &letters,
+            &letters[..corpus.answers().len()],
+        )
+    }
+
+    /// Matrix of the corpus words with indices `guesses` against those with indices
+    /// `answers`, e.g. to search over a few candidates without the whole matrix.
+    pub fn from_corpus_words<const WORD_LENGTH: usize>(
+        corpus: &Corpus<WORD_LENGTH>,
+        guesses: &[usize],
+        answers: &[usize],
+    ) -> Self {
+        let letters = |indices: &[usize]| -> Vec<[u8; WORD_LENGTH]> {
+            indices
+                .iter()
+                .map(|&index| corpus.encoded(index).letters)
+                .collect()
+        };
+        let words = |indices: &[usize]| -> Vec<String> {
+            indices
+                .iter()
+                .map(|&index| corpus.word(index).to_string())
+                .collect()
+        };
+        Self::build(
+            corpus_key(WORD_LENGTH, &words(guesses), &words(answers)),
+            &letters(guesses),
+            &letters(answers),
+        )
+    }
+
+    fn build<T: Copy + PartialEq, const WORD_LENGTH: usize>(
+        key: u64,
+        guesses: &[[T; WORD_LENGTH]],
+        answers: &[[T; WORD_LENGTH]],
+    ) -> Self {
+        assert!(WORD_LENGTH <= MAX_WORD_LENGTH, "word is too long");
+        let mut patterns = Vec::with_capacity(guesses.len() * answers.len());
+        for guess in guesses {
+            patterns.extend(
+                answers
+                    .iter()
+                    .map(|answer| encode(&feedback(guess, answer))),
+            );
+        }
+
+        Self {
+            word_length: WORD_LENGTH,
+            key,
+            guesses: guesses.len(),
+            answers: answers.len(),
+            patterns,
+        }
+    }
+
+    /// Loads the matrix cached at `path`, or builds it and refreshes the cache when the
+    /// file is missing or was made for other word lists.
+    pub fn load_or_build<const WORD_LENGTH: usize>(
+        path: impl AsRef<Path>,
+        corpus: &Corpus<WORD_LENGTH>,
+    ) -> io::Result<Self> {
+        let key = corpus_key(WORD_LENGTH, corpus.guesses(), corpus.answers());
+        match Self::load(&path, WORD_LENGTH, key) {
+            Ok(matrix) => Ok(matrix),
+            Err(PatternCacheError::Io(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            Err(_) => {
+                let matrix = Self::from_corpus(corpus);
+                matrix.save(&path)?;
+                Ok(matrix)
+            }
+        }
+    }
+
+    pub fn load(
+        path: impl AsRef<Path>,
+        word_length: usize,
+        key: u64,
+    ) -> Result<Self, PatternCacheError> {
+        Self::read_from(BufReader::new(File::open(path)?), word_length, key)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(CACHE_MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.word_length as u32).to_le_bytes())?;
+        writer.write_all(&self.key.to_le_bytes())?;
+        writer.write_all(&(self.guesses as u64).to_le_bytes())?;
+        writer.write_all(&(self.answers as u64).to_le_bytes())?;
+        for pattern in &self.patterns {
+            writer.write_all(&pattern.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(
+        mut reader: impl Read,
+        word_length: usize,
+        key: u64,
+    ) -> Result<Self, PatternCacheError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != CACHE_MAGIC {
+            return Err(PatternCacheError::Format);
+        }
+        let version = u32::from_le_bytes(read_array(&mut reader)?);
+        if version != CACHE_VERSION {
+            return Err(PatternCacheError::Version { found: version });
+        }
+        let cached_length = u32::from_le_bytes(read_array(&mut reader)?) as usize;
+        let cached_key = u64::from_le_bytes(read_array(&mut reader)?);
+        if cached_length != word_length || cached_key != key {
+            return Err(PatternCacheError::Stale);
+        }
+        let guesses = u64::from_le_bytes(read_array(&mut reader)?) as usize;
+        let answers = u64::from_le_bytes(read_array(&mut reader)?) as usize;
+
+        let size = guesses
+            .checked_mul(answers)
+            .and_then(|patterns| patterns.checked_mul(2))
+            .ok_or(PatternCacheError::Format)?;
+
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() != size {
+            return Err(PatternCacheError::Format);
+        }
+        let patterns = bytes
+            .chunks_exact(2)
+            .map(|pair| Pattern::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+
+        Ok(Self {
+            word_length,
+            key,
+            guesses,
+            answers,
+            patterns,
+        })
+    }
+
+    pub fn word_length(&self) -> usize {
+        self.word_length
+    }
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    pub fn guesses(&self) -> usize {
+        self.guesses
+    }
+
+    pub fn answers(&self) -> usize {
+        self.answers
+    }
+
+    pub fn get(&self, guess: usize, answer: usize) -> Pattern {
+        self.patterns[guess * self.answers + answer]
+    }
+
+    pub fn row(&self, guess: usize) -> &[Pattern] {
+        &self.patterns[guess * self.answers..(guess + 1) * self.answers]
+    }
+
+    /// Groups `answers` by the pattern `guess` gets against them, in pattern order.
+    pub fn partition(&self, guess: usize, answers: &[usize]) -> Vec<(Pattern, Vec<usize>)> {
+        let row = self.row(guess);
+        let mut patterns: Vec<(Pattern, usize)> = answers
+            .iter()
+            .map(|&answer| (row[answer], answer))
+            .collect();
+        patterns.sort_unstable();
+
+        let mut buckets: Vec<(Pattern, Vec<usize>)> = vec![];
+        for (pattern, answer) in patterns {
+            match buckets.last_mut() {
+                Some((last, bucket)) if *last == pattern => bucket.push(answer),
+                _ => buckets.push((pattern, vec![answer])),
+            }
+        }
+        buckets
+    }
+}
+
+fn read_array<const SIZE: usize>(reader: &mut impl Read) -> io::Result<[u8; SIZE]> {
+    let mut bytes = [0; SIZE];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[derive(Debug)]
+pub enum PatternCacheError {
+    Io(io::Error),
+    Format,
+    Version { found: u32 },
+    Stale,
+}
+
+impl From<io::Error> for PatternCacheError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => PatternCacheError::Format,
+            _ => PatternCacheError::Io(e),
+        }
+    }
+}
+
+impl Error for PatternCacheError {}
+
+impl Display for PatternCacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternCacheError::Io(e) => write!(f, "I/O error: {}", e),
+            PatternCacheError::Format => write!(f, "Not a pattern cache file"),
+            PatternCacheError::Version { found } => {
+                write!(f, "Unsupported pattern cache version {}", found)
+            }
+            PatternCacheError::Stale => write!(f, "Pattern cache was built for another corpus"),
+        }
+    }
+}
+
+/// Reusable scratch space for splitting answers into groups by the pattern they produce.
+pub struct BucketCounter {
+    counts: Vec<usize>,
+    weights: Vec<f64>,
+    touched: Vec<Pattern>,
+}
+
+impl BucketCounter {
+    pub fn new(matrix: &PatternMatrix) -> Self {
+        Self {
+            counts: vec![0; pattern_count(matrix.word_length())],
+            weights: vec![0.0; pattern_count(matrix.word_length())],
+            touched: vec![],
+        }
+    }
+
+    /// Sizes and total weights of the non-empty groups `answers` fall into when `guess` is
+    /// played. `weights` is indexed by answer.
+    pub fn count<'a>(
+        &'a mut self,
+        matrix: &PatternMatrix,
+        guess: usize,
+        answers: &[usize],
+        weights: &[f64],
+    ) -> impl Iterator<Item = (Pattern, usize, f64)> + 'a {
+        for pattern in self.touched.drain(..) {
+            self.counts[pattern as usize] = 0;
+            self.weights[pattern as usize] = 0.0;
+        }
+        let row = matrix.row(guess);
+        for &answer in answers {
+            let pattern = row[answer];
+            if self.counts[pattern as usize] == 0 {
+                self.touched.push(pattern);
+            }
+            self.counts[pattern as usize] += 1;
+            self.weights[pattern as usize] += weights[answer];
+        }
+        let counts = &self.counts;
+        let weights = &self.weights;
+        self.touched.iter().map(move |pattern| {
+            let pattern = *pattern;
+            (pattern, counts[pattern as usize], weights[pattern as usize])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::fixed_size_wordle_solver::score;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn matrix_matches_score() {
+        let words = words(&["метро", "театр", "отток", "рокот"]);
+        let matrix = PatternMatrix::new::<5>(&words, &words);
+
+        for (g, guess) in words.iter().enumerate() {
+            for (a, answer) in words.iter().enumerate() {
+                let line = score::<5>(guess, answer);
+                assert_eq!(pattern_of(&line), matrix.get(g, a));
+                assert_eq!(line.chars.map(|(_, s)| s), decode::<5>(matrix.get(g, a)));
+            }
+            assert_eq!(solved_pattern(5), matrix.get(g, g));
+        }
+    }
+
+    #[test]
+    fn cache_round_trip_and_invalidation() {
+        let words = words(&["метро", "театр", "отток"]);
+        let matrix = PatternMatrix::new::<5>(&words, &words);
+        let mut bytes = vec![];
+        matrix.write_to(&mut bytes).unwrap();
+
+        let key = corpus_key(5, &words, &words);
+        let loaded = PatternMatrix::read_from(bytes.as_slice(), 5, key).unwrap();
+        assert_eq!(matrix.patterns, loaded.patterns);
+        assert_eq!(3, loaded.guesses());
+        assert_eq!(3, loaded.answers());
+
+        let changed = corpus_key(5, &words[..2], &words[..2]);
+        assert!(matches!(
+            PatternMatrix::read_from(bytes.as_slice(), 5, changed),
+            Err(PatternCacheError::Stale)
+        ));
+        assert!(matches!(
+            PatternMatrix::read_from(&bytes[..bytes.len() - 1], 5, key),
+            Err(PatternCacheError::Format)
+        ));
+        assert!(matches!(
+            PatternMatrix::read_from(&bytes[..10], 5, key),
+            Err(PatternCacheError::Format)
+        ));
+
+        // guess count of a corrupt header, overflowing the matrix size
+        let header = 4 + 4 + 4 + 8;
+        bytes[header..header + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            PatternMatrix::read_from(bytes.as_slice(), 5, key),
+            Err(PatternCacheError::Format)
+        ));
+    }
+}
@@ -0,0 +1,424 @@
+use std::cmp::Ordering;
+
+use super::pattern_matrix::{solved_pattern, BucketCounter, Pattern, PatternMatrix};
+
+/// How good a guess is against the current candidates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuessMetric {
+    /// Expected information of the feedback, in bits, weighing candidates by how likely
+    /// they are to be the answer.
+    pub entropy: f64,
+    /// Candidates left in the worst case.
+    pub largest_bucket: usize,
+    /// Candidates left on average.
+    pub expected_remaining: f64,
+    /// Number of distinct feedbacks the guess can receive.
+    pub buckets: usize,
+    /// Whether the guess itself may be the answer.
+    pub is_candidate: bool,
+    /// Candidates left on average after this guess and the best follow-up for its
+    /// feedback, when a lookahead was performed.
+    pub lookahead_remaining: Option<f64>,
+    /// Letter frequency score of the guess, when it was ranked by one.
+    pub letter_score: Option<f64>,
+    /// Estimated guesses needed to find the answer, this one included, when estimated.
+    pub expected_guesses: Option<f64>,
+    /// Estimated chance to find the answer within the remaining attempts, when estimated.
+    pub win_probability: Option<f64>,
+}
+
+impl GuessMetric {
+    /// Metric for a guess splitting the candidates into groups of the given size and total
+    /// weight, `total_weight` being the weight of all candidates.
+    pub fn from_buckets(
+        buckets: impl Iterator<Item = (usize, f64)>,
+        total_weight: f64,
+        is_candidate: bool,
+    ) -> Self {
+        let mut metric = GuessMetric {
+            entropy: 0.0,
+            largest_bucket: 0,
+            expected_remaining: 0.0,
+            buckets: 0,
+            is_candidate,
+            lookahead_remaining: None,
+            letter_score: None,
+            expected_guesses: None,
+            win_probability: None,
+        };
+        for (size, weight) in buckets {
+            let p = weight / total_weight;
+            metric.entropy -= p * p.log2();
+            metric.largest_bucket = metric.largest_bucket.max(size);
+            metric.expected_remaining += p * size as f64;
+            metric.buckets += 1;
+        }
+        metric
+    }
+
+    /// Fills in `expected_guesses` and `win_probability` from the groups the guess splits
+    /// the candidates into, `solved` being the pattern of the guess itself.
+    ///
+    /// The groups left are assumed to be split by later guesses as finely as by this one,
+    /// or to be guessed one word at a time if that is better.
+    pub fn estimate_finish(
+        &mut self,
+        groups: &[(Pattern, usize, f64)],
+        solved: Pattern,
+        total_weight: f64,
+        remaining_attempts: usize,
+    ) {
+        let branching = self.buckets as f64;
+        let mut expected_guesses = 1.0;
+        let mut win_probability = 0.0;
+        for &(pattern, size, weight) in groups {
+            let p = weight / total_weight;
+            if pattern == solved {
+                win_probability += p;
+            } else {
+                expected_guesses += p * estimated_guesses(size, branching);
+                let attempts = remaining_attempts.saturating_sub(1);
+                win_probability += p * estimated_win_probability(size, branching, attempts);
+            }
+        }
+        if remaining_attempts == 0 {
+            win_probability = 0.0;
+        }
+        self.expected_guesses = Some(expected_guesses);
+        self.win_probability = Some(win_probability);
+    }
+}
+
+/// Guesses needed to find one of `size` candidates.
+fn estimated_guesses(size: usize, branching: f64) -> f64 {
+    let one_by_one = (size as f64 + 1.0) / 2.0;
+    if size <= 1 || branching <= 1.0 {
+        return one_by_one;
+    }
+    one_by_one.min(1.0 + (size as f64).ln() / branching.ln())
+}
+
+/// Chance to find one of `size` candidates in `attempts` guesses.
+fn estimated_win_probability(size: usize, branching: f64, attempts: usize) -> f64 {
+    if attempts == 0 {
+        return 0.0;
+    }
+    let one_by_one = attempts as f64 / size as f64;
+    let splitting = branching.powi(attempts as i32 - 1) / size as f64;
+    one_by_one.max(splitting).min(1.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ranking {
+    /// Most information on average first.
+    #[default]
+    Entropy,
+    /// Smallest worst-case group of remaining candidates first.
+    Minimax,
+    /// Fewest remaining candidates on average first.
+    ExpectedRemaining,
+    /// Fewest remaining candidates on average after two guesses first, looking ahead only
+    /// from the `top_k` guesses with the most information.
+    Lookahead { top_k: usize },
+    /// Fewest estimated guesses to finish first, so a likely answer can beat a guess with
+    /// more information.
+    ExpectedGuesses,
+    /// Highest estimated chance to win within the remaining attempts first.
+    WinProbability,
+}
+
+impl Ranking {
+    fn compare(&self, a: &GuessMetric, b: &GuessMetric) -> Ordering {
+        match self {
+            Ranking::Entropy => b.entropy.total_cmp(&a.entropy),
+            Ranking::Lookahead { .. } => match (a.lookahead_remaining, b.lookahead_remaining) {
+                (Some(a_remaining), Some(b_remaining)) => a_remaining.total_cmp(&b_remaining),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => b.entropy.total_cmp(&a.entropy),
+            },
+            Ranking::Minimax => a
+                .largest_bucket
+                .cmp(&b.largest_bucket)
+                .then_with(|| b.entropy.total_cmp(&a.entropy)),
+            Ranking::ExpectedRemaining => a.expected_remaining.total_cmp(&b.expected_remaining),
+            Ranking::ExpectedGuesses => estimate(a.expected_guesses, f64::INFINITY)
+                .total_cmp(&estimate(b.expected_guesses, f64::INFINITY)),
+            Ranking::WinProbability => estimate(b.win_probability, 0.0)
+                .total_cmp(&estimate(a.win_probability, 0.0))
+                .then_with(|| {
+                    estimate(a.expected_guesses, f64::INFINITY)
+                        .total_cmp(&estimate(b.expected_guesses, f64::INFINITY))
+                }),
+        }
+        .then_with(|| b.is_candidate.cmp(&a.is_candidate))
+    }
+}
+
+fn estimate(value: Option<f64>, unknown: f64) -> f64 {
+    value.unwrap_or(unknown)
+}
+
+/// Scores every guess against `candidates` and sorts them best first.
+///
+/// `is_candidate` is indexed by guess; ties go to words that may still be the answer.
+pub fn rank_guesses(
+    matrix: &PatternMatrix,
+    guesses: &[usize],
+    candidates: &[usize],
+    is_candidate: &[bool],
+    weights: &[f64],
+    ranking: Ranking,
+    remaining_attempts: usize,
+) -> Vec<(usize, GuessMetric)> {
+    let total_weight: f64 = candidates.iter().map(|&answer| weights[answer]).sum();
+    let solved = solved_pattern(matrix.word_length());
+    let estimate_finish = matches!(ranking, Ranking::ExpectedGuesses | Ranking::WinProbability);
+    let mut ranked: Vec<(usize, GuessMetric)> = map_chunks(guesses, |chunk| {
+        let mut buckets = BucketCounter::new(matrix);
+        chunk
+            .iter()
+            .map(|&guess| {
+                let groups: Vec<_> = buckets.count(matrix, guess, candidates, weights).collect();
+                let sizes = groups.iter().map(|&(_, size, weight)| (size, weight));
+                let mut metric =
+                    GuessMetric::from_buckets(sizes, total_weight, is_candidate[guess]);
+                if estimate_finish {
+                    metric.estimate_finish(&groups, solved, total_weight, remaining_attempts);
+                }
+                (guess, metric)
+            })
+            .collect()
+    });
+
+    if let Ranking::Lookahead { top_k } = ranking {
+        ranked.sort_by(|(_, a), (_, b)| Ranking::Entropy.compare(a, b));
+        let top: Vec<usize> = ranked.iter().take(top_k).map(|(guess, _)| *guess).collect();
+        let remaining = map_chunks(&top, |chunk| {
+            let mut buckets = BucketCounter::new(matrix);
+            chunk
+                .iter()
+                .map(|&first| {
+                    lookahead_remaining(matrix, &mut buckets, first, guesses, candidates, weights)
+                })
+                .collect()
+        });
+        for ((_, metric), remaining) in ranked.iter_mut().zip(remaining) {
+            metric.lookahead_remaining = Some(remaining);
+        }
+    }
+
+    ranked.sort_by(|(_, a), (_, b)| ranking.compare(a, b));
+    ranked
+}
+
+/// Applies `f` to consecutive chunks of `items` and joins the results in order.
+///
+/// With the `parallel` feature the chunks are processed on one thread per core, which
+/// does not change the result.
+#[cfg(feature = "parallel")]
+fn map_chunks<T: Send>(items: &[usize], f: impl Fn(&[usize]) -> Vec<T> + Sync) -> Vec<T> {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = ((items.len() + threads - 1) / threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| f(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("ranking thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(not(feature = "parallel"))]
+fn map_chunks<T>(items: &[usize], f: impl Fn(&[usize]) -> Vec<T>) -> Vec<T> {
+    f(items)
+}
+
+/// Candidates left on average after `first` and the best second guess for its feedback,
+/// counting none for the games either guess solves.
+fn lookahead_remaining(
+    matrix: &PatternMatrix,
+    buckets: &mut BucketCounter,
+    first: usize,
+    guesses: &[usize],
+    candidates: &[usize],
+    weights: &[f64],
+) -> f64 {
+    let total_weight: f64 = candidates.iter().map(|&answer| weights[answer]).sum();
+    let solved = solved_pattern(matrix.word_length());
+    matrix
+        .partition(first, candidates)
+        .into_iter()
+        .filter(|(pattern, _)| *pattern != solved)
+        .map(|(_, group)| {
+            let group_weight: f64 = group.iter().map(|&answer| weights[answer]).sum();
+            let best = guesses
+                .iter()
+                .map(|&second| {
+                    buckets
+                        .count(matrix, second, &group, weights)
+                        .filter(|(pattern, _, _)| *pattern != solved)
+                        .map(|(_, size, weight)| weight / group_weight * size as f64)
+                        .sum::<f64>()
+                })
+                .min_by(f64::total_cmp)
+                .unwrap_or(group.len() as f64);
+            group_weight / total_weight * best
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvers::wordle::words;
+
+    #[test]
+    fn metric_from_buckets() {
+        let buckets = [(2, 2.0), (1, 1.0), (1, 1.0)];
+        let metric = GuessMetric::from_buckets(buckets.into_iter(), 4.0, true);
+
+        assert!((metric.entropy - 1.5).abs() < 1e-9);
+        assert!((metric.expected_remaining - 1.5).abs() < 1e-9);
+        assert_eq!(2, metric.largest_bucket);
+        assert_eq!(3, metric.buckets);
+        assert!(metric.is_candidate);
+    }
+
+    #[test]
+    fn weights_shift_information() {
+        // the likely group barely tells anything apart
+        let buckets = [(1, 9.0), (1, 0.5), (1, 0.5)];
+        let metric = GuessMetric::from_buckets(buckets.into_iter(), 10.0, false);
+
+        assert!(metric.entropy < 1.0);
+        assert!((metric.expected_remaining - 1.0).abs() < 1e-9);
+        assert_eq!(3, metric.buckets);
+    }
+
+    #[test]
+    fn chunks_keep_item_order() {
+        let items: Vec<usize> = (0..1000).collect();
+        let doubled = map_chunks(&items, |chunk| chunk.iter().map(|i| i * 2).collect());
+        assert_eq!(items.iter().map(|i| i * 2).collect::<Vec<_>>(), doubled);
+    }
+
+    #[test]
+    fn lookahead_only_for_top_guesses() {
+        let words = words(&["abc", "abd", "abe", "cde"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let all: Vec<usize> = (0..words.len()).collect();
+
+        let ranked = rank_guesses(
+            &matrix,
+            &all,
+            &all,
+            &[true; 4],
+            &[1.0; 4],
+            Ranking::Lookahead { top_k: 2 },
+            6,
+        );
+
+        // "cde" leaves a single word for every other answer, found by the second guess
+        assert_eq!(3, ranked[0].0);
+        assert_eq!(Some(0.0), ranked[0].1.lookahead_remaining);
+        assert!(ranked[1].1.lookahead_remaining.is_some());
+        assert!(ranked[2..]
+            .iter()
+            .all(|(_, metric)| metric.lookahead_remaining.is_none()));
+    }
+
+    #[test]
+    fn lookahead_counts_a_win_at_either_guess_alike() {
+        let words = words(&["abc", "abd", "abe", "xyz", "xyw", "cbq"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let mut is_candidate = [true; 6];
+        is_candidate[5] = false;
+
+        let ranked = rank_guesses(
+            &matrix,
+            &all,
+            &all[..5],
+            &is_candidate,
+            &[1.0; 6],
+            Ranking::Lookahead { top_k: 6 },
+            6,
+        );
+
+        // "cbq" splits the candidates just like "abc" does, but "abc" may win at once
+        let remaining = |guess| {
+            let (_, metric) = ranked.iter().find(|(g, _)| *g == guess).unwrap();
+            metric.lookahead_remaining.unwrap()
+        };
+        assert!((remaining(0) - 0.4).abs() < 1e-9);
+        assert!((remaining(5) - 0.4).abs() < 1e-9);
+        assert_eq!(0, ranked[0].0);
+    }
+
+    #[test]
+    fn lookahead_is_attached_to_its_own_guess() {
+        // enough guesses to be split between threads with the `parallel` feature
+        let letters = ["a", "b", "c", "d", "e", "f"];
+        let words: Vec<String> = letters
+            .iter()
+            .flat_map(|x| letters.iter().map(move |y| format!("{}{}a", x, y)))
+            .chain(letters.iter().map(|x| format!("{}{}{}", x, x, x)))
+            .collect();
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let is_candidate = vec![true; words.len()];
+        let weights = vec![1.0; words.len()];
+
+        let top_k = 7;
+        let ranking = Ranking::Lookahead { top_k };
+        let ranked = rank_guesses(&matrix, &all, &all, &is_candidate, &weights, ranking, 6);
+
+        let mut by_entropy = ranked.clone();
+        by_entropy.sort_by(|(_, a), (_, b)| Ranking::Entropy.compare(a, b));
+        let mut buckets = BucketCounter::new(&matrix);
+        for (position, (guess, metric)) in by_entropy.iter().enumerate() {
+            let expected = (position < top_k)
+                .then(|| lookahead_remaining(&matrix, &mut buckets, *guess, &all, &all, &weights));
+            assert_eq!(expected, metric.lookahead_remaining);
+        }
+    }
+
+    #[test]
+    fn finish_objectives_weigh_guessing_the_answer() {
+        let words = words(&["abc", "abd", "abe", "cde"]);
+        let matrix = PatternMatrix::new::<3>(&words, &words);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let is_candidate = [true, true, true, false];
+        let rank = |candidates: &[usize], ranking, remaining_attempts| {
+            rank_guesses(
+                &matrix,
+                &all,
+                candidates,
+                &is_candidate,
+                &[1.0; 4],
+                ranking,
+                remaining_attempts,
+            )
+        };
+
+        // "abc" may win at once, "cde" only tells the two words apart
+        let by_guesses = rank(&all[..2], Ranking::ExpectedGuesses, 6);
+        assert_eq!(0, by_guesses[0].0);
+        assert_eq!(Some(1.5), by_guesses[0].1.expected_guesses);
+        let probe = by_guesses.iter().find(|(guess, _)| *guess == 3).unwrap();
+        assert_eq!(Some(2.0), probe.1.expected_guesses);
+
+        // but among three words only "cde" is sure to leave one for the last attempt
+        let by_wins = rank(&all[..3], Ranking::WinProbability, 2);
+        assert_eq!(3, by_wins[0].0);
+        assert_eq!(Some(1.0), by_wins[0].1.win_probability);
+        assert!((by_wins[1].1.win_probability.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        let last_attempt = rank(&all[..3], Ranking::WinProbability, 1);
+        assert_eq!(Some(0.0), last_attempt[3].1.win_probability);
+    }
+}
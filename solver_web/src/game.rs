@@ -1,28 +1,177 @@
-use yew::{html, Component, Properties};
+use web_sys::HtmlInputElement;
+use wordle_searcher::solvers::{
+    strategy::StrategyRegistry,
+    wordle::{
+        dynamic_wordle_solver::{DynamicCorpus, DynamicWordleLine, WordleSolver},
+        fixed_size_wordle_solver::WordleCharStatus,
+    },
+    Solver,
+};
+use yew::{html, Component, NodeRef, Properties};
 
 pub struct Game {
-    wordle: NaiveSolver<5, 6>,
+    wordle: WordleSolver,
+    strategies: StrategyRegistry,
+    word_ref: NodeRef,
+    mask_ref: NodeRef,
+    error: Option<String>,
 }
 #[derive(Properties, PartialEq)]
 pub struct GameProps {
-    corpus: Vec<String>,
+    pub corpus: DynamicCorpus,
 }
+pub enum GameMsg {
+    /// Feedback for a played guess.
+    Guess(DynamicWordleLine),
+    /// Guess that could not be read from the form.
+    InvalidGuess(String),
+    /// Name of the strategy to recommend guesses with.
+    Strategy(String),
+}
+
+/// Reads a guess the way the terminal version does: the mask has `*` for a missing
+/// letter, `?` for a misplaced one and the letter itself where it is right.
+fn parse_guess(word: &str, mask: &str, word_length: usize) -> Result<DynamicWordleLine, String> {
+    if word.chars().count() != mask.chars().count() {
+        return Err("Слово и маска разной длины".to_string());
+    }
+    if word.chars().count() != word_length {
+        return Err(format!("Нужно слово из {} букв", word_length));
+    }
+    let chars = word
+        .chars()
+        .zip(mask.chars())
+        .map(|(ch, mask_ch)| match mask_ch {
+            '*' => Ok((ch, WordleCharStatus::Inexistent)),
+            '?' => Ok((ch, WordleCharStatus::Existing)),
+            _ if mask_ch == ch => Ok((ch, WordleCharStatus::Good)),
+            _ => Err(format!("Непонятный символ маски: {}", mask_ch)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(DynamicWordleLine::new(chars))
+}
+
 impl Component for Game {
-    type Message = ();
+    type Message = GameMsg;
 
     type Properties = GameProps;
 
     fn create(ctx: &yew::Context<Self>) -> Self {
+        let strategies = StrategyRegistry::with_builtin();
+        let mut wordle = WordleSolver::from_shared_corpus(&ctx.props().corpus);
+        // full entropy is too slow in the browser
+        if let Some(frequency) = strategies.get("frequency") {
+            wordle.set_strategy(frequency);
+        }
         Game {
-            // TODO excessive clone
-            wordle: NaiveSolver::new(ctx.props().corpus.clone()),
+            wordle,
+            strategies,
+            word_ref: NodeRef::default(),
+            mask_ref: NodeRef::default(),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            GameMsg::Guess(line) => {
+                self.error = match self.wordle.add_constraint(line) {
+                    Ok(()) => {
+                        for input in [&self.word_ref, &self.mask_ref] {
+                            if let Some(input) = input.cast::<HtmlInputElement>() {
+                                input.set_value("");
+                            }
+                        }
+                        None
+                    }
+                    Err(error) => Some(error.to_string()),
+                };
+                true
+            }
+            GameMsg::InvalidGuess(error) => {
+                self.error = Some(error);
+                true
+            }
+            GameMsg::Strategy(name) => match self.strategies.get(&name) {
+                Some(strategy) => {
+                    self.wordle.set_strategy(strategy);
+                    true
+                }
+                None => false,
+            },
         }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let current = self.wordle.strategy().name();
+        let strategies = self.strategies.iter().map(|strategy| {
+            let name = strategy.name().to_string();
+            let onclick = ctx
+                .link()
+                .callback(move |_| GameMsg::Strategy(name.clone()));
+            html! {
+                <button
+                    class={if strategy.name() == current {"selected"} else {""}}
+                    title={strategy.description().to_string()}
+                    {onclick}>
+                    {strategy.name()}
+                </button>
+            }
+        });
+        let onclick = {
+            let word_ref = self.word_ref.clone();
+            let mask_ref = self.mask_ref.clone();
+            let word_length = self.wordle.word_length();
+            ctx.link().callback(move |_| {
+                let value = |input: &NodeRef| {
+                    input
+                        .cast::<HtmlInputElement>()
+                        .map(|input| input.value().trim().to_lowercase())
+                        .unwrap_or_default()
+                };
+                match parse_guess(&value(&word_ref), &value(&mask_ref), word_length) {
+                    Ok(line) => GameMsg::Guess(line),
+                    Err(error) => GameMsg::InvalidGuess(error),
+                }
+            })
+        };
+        let history = self.wordle.history().into_iter().map(|line| {
+            let mask: String = line
+                .chars
+                .iter()
+                .map(|(ch, status)| match status {
+                    WordleCharStatus::Inexistent => '*',
+                    WordleCharStatus::Existing => '?',
+                    WordleCharStatus::Good => *ch,
+                })
+                .collect();
+            html! { <li>{format!("{} {}", line.word(), mask)}</li> }
+        });
+        let recommendations = self.wordle.iter_best_next_guess().take(10);
         html! {
             <div>
-             {"TODO"}
+                <div class="strategies">
+                    { for strategies }
+                </div>
+                <ol class="history">
+                    { for history }
+                </ol>
+                <form>
+                    <input type="text" name="guess" placeholder="слово" ref={self.word_ref.clone()}/>
+                    <input
+                        type="text" name="mask"
+                        placeholder="маска: * - нет, ? - не на месте"
+                        ref={self.mask_ref.clone()}
+                        />
+                    <input type="button" name="add-guess" value="Добавить" {onclick}/>
+                </form>
+                if let Some(error) = &self.error {
+                    <p class="error">{error}</p>
+                }
+                <p>{format!("Всего {}", self.wordle.possible_guess_count())}</p>
+                <ul>
+                    { for recommendations.map(|(word, _)| html! { <li>{word}</li> }) }
+                </ul>
             </div>
         }
     }
@@ -2,7 +2,11 @@ mod corpus_loader;
 mod game;
 use corpus_loader::*;
 use game::*;
-use yew::{function_component, html, use_state, Callback};
+use web_sys::HtmlInputElement;
+use wordle_searcher::solvers::wordle::dynamic_wordle_solver::{
+    DynamicCorpus, MAX_WORD_LENGTH, MIN_WORD_LENGTH,
+};
+use yew::{function_component, html, use_state, Callback, Event, TargetCast};
 // load corpus
 // - input
 // - button
@@ -21,27 +25,43 @@ use yew::{function_component, html, use_state, Callback};
 
 #[function_component(App)]
 fn app() -> Html {
-    let loaded = use_state(|| false);
-    let corpus = use_state(|| Option::None as Option<Vec<String>>);
+    // encoded once and shared by every game played on it
+    let corpus = use_state(|| Option::None as Option<DynamicCorpus>);
+    let word_length = use_state(|| 5);
 
     let callback = {
-        let loaded = loaded.clone();
         let corpus = corpus.clone();
-        Callback::from(move |data| {
-            corpus.set(Some(data));
-            loaded.set(true)
+        let word_length = word_length.clone();
+        Callback::from(move |data| corpus.set(DynamicCorpus::new(*word_length, data).ok()))
+    };
+    let on_length_changed = {
+        let word_length = word_length.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(length) = input.value().parse::<usize>() {
+                word_length.set(length.clamp(MIN_WORD_LENGTH, MAX_WORD_LENGTH));
+            }
         })
     };
 
     html! {
         <div>
-            if !*loaded {
+            if let Some(corpus) = &*corpus {
+                <Game corpus={corpus.clone()}/>
+            } else {
+                <label>
+                    {"Букв в слове: "}
+                    <input
+                        type="number" name="word-length"
+                        min={MIN_WORD_LENGTH.to_string()} max={MAX_WORD_LENGTH.to_string()}
+                        value={word_length.to_string()}
+                        onchange={on_length_changed}
+                        />
+                </label>
                 <CorpusLoader
                     default_url={"https://raw.githubusercontent.com/Harrix/Russian-Nouns/main/dist/russian_nouns.txt".to_string()}
                     on_loaded={callback}
                 />
-            } else {
-                // <Game/>
             }
         </div>
     }